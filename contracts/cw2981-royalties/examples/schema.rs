@@ -7,9 +7,14 @@ use cw721::{
     AllNftInfoResponse, ContractInfoResponse, NftInfoResponse, NumTokensResponse,
     OperatorsResponse, OwnerOfResponse, TokensResponse,
 };
-use cw721_base::{ExecuteMsg, Extension, InstantiateMsg, MinterResponse, QueryMsg};
+use cw721_base::{Extension, MinterResponse};
 
-use cw2981_royalties::msg::{CheckRoyaltiesResponse, Cw2981QueryMsg, RoyaltiesInfoResponse};
+use cw2981_royalties::msg::{
+    CheckRoyaltiesResponse, Cw2981ExecuteMsg, Cw2981QueryMsg, DefaultRoyaltyResponse,
+    EffectiveRoyaltyResponse, InstantiateMsg, RoyaltiesInfoResponse, RoyaltyConfigResponse,
+    RoyaltyInfoSplitResponse,
+};
+use cw2981_royalties::QueryMsg;
 
 fn main() {
     let mut out_dir = current_dir().unwrap();
@@ -18,8 +23,8 @@ fn main() {
     remove_schemas(&out_dir).unwrap();
 
     export_schema(&schema_for!(InstantiateMsg), &out_dir);
-    export_schema_with_title(&schema_for!(ExecuteMsg<Extension>), &out_dir, "ExecuteMsg");
-    export_schema(&schema_for!(QueryMsg), &out_dir);
+    export_schema_with_title(&schema_for!(Cw2981ExecuteMsg), &out_dir, "ExecuteMsg");
+    export_schema_with_title(&schema_for!(QueryMsg), &out_dir, "QueryMsg");
     export_schema_with_title(
         &schema_for!(AllNftInfoResponse<Extension>),
         &out_dir,
@@ -38,5 +43,9 @@ fn main() {
     export_schema(&schema_for!(TokensResponse), &out_dir);
     export_schema(&schema_for!(Cw2981QueryMsg), &out_dir);
     export_schema(&schema_for!(RoyaltiesInfoResponse), &out_dir);
+    export_schema(&schema_for!(RoyaltyInfoSplitResponse), &out_dir);
     export_schema(&schema_for!(CheckRoyaltiesResponse), &out_dir);
+    export_schema(&schema_for!(DefaultRoyaltyResponse), &out_dir);
+    export_schema(&schema_for!(EffectiveRoyaltyResponse), &out_dir);
+    export_schema(&schema_for!(RoyaltyConfigResponse), &out_dir);
 }
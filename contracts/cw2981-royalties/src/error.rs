@@ -0,0 +1,29 @@
+use cosmwasm_std::StdError;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("{0}")]
+    Base(#[from] cw721_base::ContractError),
+
+    #[error("Unauthorized")]
+    Unauthorized {},
+
+    #[error("Royalty percentage must not exceed 100, got {percentage}")]
+    InvalidRoyaltyPercentage { percentage: u64 },
+
+    #[error("Royalty must not exceed 10000 basis points, got {basis_points}")]
+    InvalidRoyaltyBasisPoints { basis_points: u64 },
+
+    #[error("Royalty share of {address} must have a weight above zero")]
+    InvalidRoyaltyShare { address: String },
+
+    #[error("Royalty share weights must not add up to more than {}", u64::MAX)]
+    RoyaltyWeightOverflow {},
+
+    #[error("Royalty schedule thresholds of the same kind must be in ascending order")]
+    UnorderedRoyaltySchedule {},
+}
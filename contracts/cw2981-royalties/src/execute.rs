@@ -0,0 +1,188 @@
+use cosmwasm_std::{Deps, DepsMut, Env, MessageInfo, Response, StdResult, Storage};
+
+use crate::error::ContractError;
+use crate::state::{
+    DefaultRoyalty, RoyaltyConfig, RoyaltyShare, RoyaltyStep, DEFAULT_ROYALTY, RESALES,
+    ROYALTY_CONFIG, ROYALTY_TOKENS,
+};
+use crate::{schedule_in_order, Cw2981Contract, Extension, Metadata, MAX_BASIS_POINTS};
+
+/// Runs a cw721 message, validating the royalty of minted tokens
+/// and keeping count of the tokens that set one
+pub fn execute_base(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: cw721_base::ExecuteMsg<Extension>,
+) -> Result<Response, ContractError> {
+    let contract = Cw2981Contract::default();
+    // Some(true) when a token with a royalty is minted, Some(false) when one is burned
+    let royalty_token_added = match &msg {
+        cw721_base::ExecuteMsg::Mint(mint_msg) => {
+            if let Some(metadata) = &mint_msg.extension {
+                validate_metadata(deps.as_ref(), metadata)?;
+            }
+            if has_royalty(&mint_msg.extension) {
+                Some(true)
+            } else {
+                None
+            }
+        }
+        cw721_base::ExecuteMsg::Burn { token_id } => {
+            RESALES.remove(deps.storage, token_id);
+            match contract.tokens.may_load(deps.storage, token_id)? {
+                Some(token) if has_royalty(&token.extension) => Some(false),
+                _ => None,
+            }
+        }
+        _ => None,
+    };
+
+    let response = contract.execute_indexed(deps.branch(), env, info, msg)?;
+    if let Some(added) = royalty_token_added {
+        update_royalty_tokens(deps.storage, added)?;
+    }
+    Ok(response)
+}
+
+fn has_royalty(extension: &Extension) -> bool {
+    matches!(extension, Some(metadata) if metadata.has_royalty())
+}
+
+/// Keeps count of the tokens with a royalty, unless the contract predates the count
+fn update_royalty_tokens(storage: &mut dyn Storage, added: bool) -> StdResult<()> {
+    if let Some(count) = ROYALTY_TOKENS.may_load(storage)? {
+        let count = if added {
+            count + 1
+        } else {
+            count.saturating_sub(1)
+        };
+        ROYALTY_TOKENS.save(storage, &count)?;
+    }
+    Ok(())
+}
+
+/// Sets or removes the default royalty, can only be called by the minter
+pub fn update_default_royalty(
+    deps: DepsMut,
+    info: MessageInfo,
+    default_royalty: Option<DefaultRoyalty>,
+) -> Result<Response, ContractError> {
+    let minter = Cw2981Contract::default().minter.load(deps.storage)?;
+    if info.sender != minter {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut response = Response::new().add_attribute("action", "update_default_royalty");
+    match default_royalty {
+        Some(default_royalty) => {
+            validate_default_royalty(deps.as_ref(), &default_royalty)?;
+            DEFAULT_ROYALTY.save(deps.storage, &default_royalty)?;
+            response = response
+                .add_attribute("basis_points", default_royalty.royalty_bps().to_string())
+                .add_attribute("payment_address", default_royalty.payment_address);
+        }
+        None => DEFAULT_ROYALTY.remove(deps.storage),
+    }
+    Ok(response)
+}
+
+/// Turns royalties on or off, can only be called by the minter
+pub fn update_royalty_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    royalties_enabled: bool,
+) -> Result<Response, ContractError> {
+    let minter = Cw2981Contract::default().minter.load(deps.storage)?;
+    if info.sender != minter {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    ROYALTY_CONFIG.save(deps.storage, &RoyaltyConfig { royalties_enabled })?;
+    Ok(Response::new()
+        .add_attribute("action", "update_royalty_config")
+        .add_attribute("royalties_enabled", royalties_enabled.to_string()))
+}
+
+pub fn validate_default_royalty(
+    deps: Deps,
+    default_royalty: &DefaultRoyalty,
+) -> Result<(), ContractError> {
+    validate_royalty(
+        Some(default_royalty.percentage),
+        default_royalty.basis_points,
+        &default_royalty.schedule,
+    )?;
+    deps.api.addr_validate(&default_royalty.payment_address)?;
+    validate_royalty_shares(deps, &default_royalty.recipients)
+}
+
+/// Checks the royalty fields of a token
+pub fn validate_metadata(deps: Deps, metadata: &Metadata) -> Result<(), ContractError> {
+    validate_royalty(
+        metadata.royalty_percentage,
+        metadata.royalty_basis_points,
+        metadata.royalty_schedule.as_deref().unwrap_or_default(),
+    )?;
+    if let Some(recipients) = &metadata.royalty_recipients {
+        validate_royalty_shares(deps, recipients)?;
+    }
+    Ok(())
+}
+
+/// Checks a royalty rate and its schedule. Also used by contracts keeping
+/// royalty settings of their own, so they are held to the same limits
+pub fn validate_royalty(
+    percentage: Option<u64>,
+    basis_points: Option<u64>,
+    schedule: &[RoyaltyStep],
+) -> Result<(), ContractError> {
+    if let Some(percentage) = percentage {
+        validate_royalty_percentage(percentage)?;
+    }
+    if let Some(basis_points) = basis_points {
+        validate_royalty_basis_points(basis_points)?;
+    }
+    validate_royalty_schedule(schedule)
+}
+
+pub fn validate_royalty_schedule(schedule: &[RoyaltyStep]) -> Result<(), ContractError> {
+    for step in schedule {
+        validate_royalty_basis_points(step.basis_points)?;
+    }
+    if !schedule_in_order(schedule) {
+        return Err(ContractError::UnorderedRoyaltySchedule {});
+    }
+    Ok(())
+}
+
+pub fn validate_royalty_shares(deps: Deps, shares: &[RoyaltyShare]) -> Result<(), ContractError> {
+    // the weights are summed to split each royalty
+    let mut total_weight = 0u64;
+    for share in shares {
+        deps.api.addr_validate(&share.address)?;
+        if share.weight == 0 {
+            return Err(ContractError::InvalidRoyaltyShare {
+                address: share.address.clone(),
+            });
+        }
+        total_weight = total_weight
+            .checked_add(share.weight)
+            .ok_or(ContractError::RoyaltyWeightOverflow {})?;
+    }
+    Ok(())
+}
+
+pub fn validate_royalty_percentage(percentage: u64) -> Result<(), ContractError> {
+    if percentage > 100 {
+        return Err(ContractError::InvalidRoyaltyPercentage { percentage });
+    }
+    Ok(())
+}
+
+pub fn validate_royalty_basis_points(basis_points: u64) -> Result<(), ContractError> {
+    if basis_points > MAX_BASIS_POINTS {
+        return Err(ContractError::InvalidRoyaltyBasisPoints { basis_points });
+    }
+    Ok(())
+}
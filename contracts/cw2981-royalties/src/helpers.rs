@@ -0,0 +1,318 @@
+use cosmwasm_std::{
+    to_binary, Addr, BankMsg, Coin, CosmosMsg, QuerierWrapper, StdResult, Uint128, WasmMsg,
+    WasmQuery,
+};
+use cw20::{Cw20ExecuteMsg, Denom};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::msg::{
+    CheckRoyaltiesResponse, Cw2981QueryMsg, RoyaltiesInfoResponse, RoyaltyInfoSplitResponse,
+};
+use crate::QueryMsg;
+
+/// Helper for marketplaces selling tokens of a cw2981 contract
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Cw2981Contract(pub Addr);
+
+impl Cw2981Contract {
+    pub fn addr(&self) -> Addr {
+        self.0.clone()
+    }
+
+    pub fn query<T: DeserializeOwned>(
+        &self,
+        querier: &QuerierWrapper,
+        req: Cw2981QueryMsg,
+    ) -> StdResult<T> {
+        let query = WasmQuery::Smart {
+            contract_addr: self.addr().into(),
+            msg: to_binary(&QueryMsg::Extension { msg: req })?,
+        }
+        .into();
+        querier.query(&query)
+    }
+
+    /*** queries ***/
+
+    pub fn check_royalties(&self, querier: &QuerierWrapper) -> StdResult<bool> {
+        let res: CheckRoyaltiesResponse = self.query(querier, Cw2981QueryMsg::CheckRoyalties {})?;
+        Ok(res.royalty_payments)
+    }
+
+    pub fn royalty_info<T: Into<String>>(
+        &self,
+        querier: &QuerierWrapper,
+        token_id: T,
+        sale_price: Uint128,
+    ) -> StdResult<RoyaltiesInfoResponse> {
+        let req = Cw2981QueryMsg::RoyaltyInfo {
+            token_id: token_id.into(),
+            sale_price,
+        };
+        self.query(querier, req)
+    }
+
+    pub fn royalty_info_split<T: Into<String>>(
+        &self,
+        querier: &QuerierWrapper,
+        token_id: T,
+        sale_price: Uint128,
+    ) -> StdResult<RoyaltyInfoSplitResponse> {
+        let req = Cw2981QueryMsg::RoyaltyInfoSplit {
+            token_id: token_id.into(),
+            sale_price,
+        };
+        self.query(querier, req)
+    }
+
+    /*** payouts ***/
+
+    /// Builds the messages paying out a sale of `token_id` for `sale_price` of `denom`:
+    /// one per royalty recipient, and the rest to the seller.
+    /// If the contract doesn't ask for royalties, the seller gets everything
+    pub fn sale_payouts<T: Into<String>, U: Into<String>>(
+        &self,
+        querier: &QuerierWrapper,
+        token_id: T,
+        seller: U,
+        denom: &Denom,
+        sale_price: Uint128,
+    ) -> StdResult<Vec<CosmosMsg>> {
+        let payments = if self.check_royalties(querier)? {
+            self.royalty_info_split(querier, token_id, sale_price)?
+                .payments
+        } else {
+            vec![]
+        };
+
+        let mut messages = vec![];
+        let mut seller_amount = sale_price;
+        for payment in payments {
+            let amount = payment.amount.min(seller_amount);
+            if amount.is_zero() {
+                continue;
+            }
+            messages.push(payout(denom, payment.address, amount)?);
+            seller_amount -= amount;
+        }
+        if !seller_amount.is_zero() {
+            messages.push(payout(denom, seller.into(), seller_amount)?);
+        }
+        Ok(messages)
+    }
+}
+
+/// Sends `amount` of `denom` to `recipient`
+pub fn payout(denom: &Denom, recipient: String, amount: Uint128) -> StdResult<CosmosMsg> {
+    Ok(match denom {
+        Denom::Native(denom) => BankMsg::Send {
+            to_address: recipient,
+            amount: vec![Coin {
+                denom: denom.clone(),
+                amount,
+            }],
+        }
+        .into(),
+        Denom::Cw20(contract) => WasmMsg::Execute {
+            contract_addr: contract.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer { recipient, amount })?,
+            funds: vec![],
+        }
+        .into(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entry;
+    use crate::state::{DefaultRoyalty, RoyaltyShare};
+    use crate::{ExecuteMsg, InstantiateMsg, Metadata, MintMsg};
+
+    use cosmwasm_std::testing::{
+        mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MockStorage,
+    };
+    use cosmwasm_std::{from_binary, from_slice, ContractResult, Empty, OwnedDeps, SystemResult};
+
+    const CREATOR: &str = "creator";
+    const ROYALTY_CONTRACT: &str = "royalties";
+    const SELLER: &str = "seller";
+
+    /// A contract with a venue/artist split default royalty and one token overriding it
+    fn royalty_contract() -> OwnedDeps<MockStorage, MockApi, MockQuerier> {
+        let mut deps = mock_dependencies();
+        let info = mock_info(CREATOR, &[]);
+        let init_msg = InstantiateMsg {
+            name: "SpaceShips".to_string(),
+            symbol: "SPACE".to_string(),
+            minter: CREATOR.to_string(),
+            soulbound: false,
+            default_royalty: Some(DefaultRoyalty {
+                percentage: 10,
+                basis_points: None,
+                payment_address: "venue".to_string(),
+                recipients: vec![
+                    RoyaltyShare {
+                        address: "venue".to_string(),
+                        weight: 2,
+                    },
+                    RoyaltyShare {
+                        address: "artist".to_string(),
+                        weight: 1,
+                    },
+                ],
+                schedule: vec![],
+            }),
+        };
+        entry::instantiate(deps.as_mut(), mock_env(), info.clone(), init_msg).unwrap();
+
+        for (token_id, extension) in [
+            ("Enterprise", None),
+            (
+                "Voyager",
+                Some(Metadata {
+                    royalty_basis_points: Some(250),
+                    royalty_payment_address: Some("janeway".to_string()),
+                    ..Metadata::default()
+                }),
+            ),
+        ] {
+            let mint_msg = MintMsg {
+                token_id: token_id.to_string(),
+                owner: SELLER.to_string(),
+                token_uri: None,
+                extension,
+            };
+            let exec_msg = ExecuteMsg::BaseMsg(cw721_base::ExecuteMsg::Mint(mint_msg));
+            entry::execute(deps.as_mut(), mock_env(), info.clone(), exec_msg).unwrap();
+        }
+        deps
+    }
+
+    /// A querier answering smart queries with the royalty contract
+    fn marketplace_querier(royalties_enabled: bool) -> MockQuerier {
+        let mut contract = royalty_contract();
+        let exec_msg = ExecuteMsg::UpdateRoyaltyConfig { royalties_enabled };
+        entry::execute(
+            contract.as_mut(),
+            mock_env(),
+            mock_info(CREATOR, &[]),
+            exec_msg,
+        )
+        .unwrap();
+
+        let mut querier = MockQuerier::<Empty>::new(&[]);
+        querier.update_wasm(move |request| {
+            let msg = match request {
+                WasmQuery::Smart { msg, .. } => from_binary(msg).unwrap(),
+                _ => panic!("unexpected query: {:?}", request),
+            };
+            let res = entry::query(contract.as_ref(), mock_env(), msg);
+            SystemResult::Ok(ContractResult::from(res))
+        });
+        querier
+    }
+
+    fn bank(to_address: &str, amount: u128) -> CosmosMsg {
+        BankMsg::Send {
+            to_address: to_address.to_string(),
+            amount: vec![Coin::new(amount, "ujuno")],
+        }
+        .into()
+    }
+
+    #[test]
+    fn native_payouts() {
+        let querier = marketplace_querier(true);
+        let querier = QuerierWrapper::new(&querier);
+        let contract = Cw2981Contract(Addr::unchecked(ROYALTY_CONTRACT));
+        let denom = Denom::Native("ujuno".to_string());
+
+        assert!(contract.check_royalties(&querier).unwrap());
+        let res = contract
+            .royalty_info(&querier, "Voyager", Uint128::new(1000))
+            .unwrap();
+        assert_eq!(
+            res,
+            RoyaltiesInfoResponse {
+                address: "janeway".to_string(),
+                royalty_amount: Uint128::new(25),
+            }
+        );
+
+        // the default royalty is split, with the dust going to the first recipient
+        let messages = contract
+            .sale_payouts(&querier, "Enterprise", SELLER, &denom, Uint128::new(101))
+            .unwrap();
+        assert_eq!(
+            messages,
+            vec![bank("venue", 7), bank("artist", 3), bank(SELLER, 91)]
+        );
+
+        let messages = contract
+            .sale_payouts(&querier, "Voyager", SELLER, &denom, Uint128::new(1000))
+            .unwrap();
+        assert_eq!(messages, vec![bank("janeway", 25), bank(SELLER, 975)]);
+
+        // nothing is sent for a free sale
+        let messages = contract
+            .sale_payouts(&querier, "Voyager", SELLER, &denom, Uint128::zero())
+            .unwrap();
+        assert!(messages.is_empty());
+    }
+
+    #[test]
+    fn cw20_payouts() {
+        let querier = marketplace_querier(true);
+        let querier = QuerierWrapper::new(&querier);
+        let contract = Cw2981Contract(Addr::unchecked(ROYALTY_CONTRACT));
+        let denom = Denom::Cw20(Addr::unchecked("token"));
+
+        let messages = contract
+            .sale_payouts(&querier, "Voyager", SELLER, &denom, Uint128::new(1000))
+            .unwrap();
+        let transfers: Vec<_> = messages
+            .into_iter()
+            .map(|msg| match msg {
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr,
+                    msg,
+                    funds,
+                }) => {
+                    assert_eq!(contract_addr, "token");
+                    assert!(funds.is_empty());
+                    from_slice::<Cw20ExecuteMsg>(&msg).unwrap()
+                }
+                msg => panic!("unexpected message: {:?}", msg),
+            })
+            .collect();
+        assert_eq!(
+            transfers,
+            vec![
+                Cw20ExecuteMsg::Transfer {
+                    recipient: "janeway".to_string(),
+                    amount: Uint128::new(25),
+                },
+                Cw20ExecuteMsg::Transfer {
+                    recipient: SELLER.to_string(),
+                    amount: Uint128::new(975),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn no_royalties_owed() {
+        let querier = marketplace_querier(false);
+        let querier = QuerierWrapper::new(&querier);
+        let contract = Cw2981Contract(Addr::unchecked(ROYALTY_CONTRACT));
+        let denom = Denom::Native("ujuno".to_string());
+
+        assert!(!contract.check_royalties(&querier).unwrap());
+        let messages = contract
+            .sale_payouts(&querier, "Voyager", SELLER, &denom, Uint128::new(1000))
+            .unwrap();
+        assert_eq!(messages, vec![bank(SELLER, 1000)]);
+    }
+}
@@ -1,14 +1,24 @@
+mod error;
+pub mod execute;
+pub mod helpers;
 pub mod msg;
 pub mod query;
+pub mod state;
 
-pub use query::{check_royalties, query_royalties_info};
+pub use crate::error::ContractError;
+pub use crate::msg::{Cw2981ExecuteMsg, Cw2981QueryMsg, InstantiateMsg};
+pub use crate::state::{DefaultRoyalty, RoyaltyShare, RoyaltyStep, RoyaltyThreshold};
+pub use query::{
+    check_royalties, query_default_royalty, query_effective_royalty, query_royalties_info,
+    query_royalty_config, query_royalty_info_split,
+};
 
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::Empty;
-use cw721_base::Cw721Contract;
-pub use cw721_base::{ContractError, InstantiateMsg, MintMsg, MinterResponse};
+use cosmwasm_std::{BlockInfo, Empty};
+use cw721_base::{Attributes, Cw721Contract};
+pub use cw721_base::{MintMsg, MinterResponse};
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
 pub struct Trait {
@@ -36,6 +46,78 @@ pub struct Metadata {
     /// as the minter addr
     /// question: how do we validate this?
     pub royalty_payment_address: Option<String>,
+    /// The royalty in basis points, a hundredth of a percent.
+    /// Takes precedence over `royalty_percentage`
+    pub royalty_basis_points: Option<u64>,
+    /// Splits the royalty between several recipients instead of paying
+    /// the payment address alone
+    pub royalty_recipients: Option<Vec<RoyaltyShare>>,
+    /// Changes the royalty over time or as the token is resold
+    pub royalty_schedule: Option<Vec<RoyaltyStep>>,
+}
+
+/// A whole royalty in basis points
+pub const MAX_BASIS_POINTS: u64 = 10_000;
+
+impl Metadata {
+    /// The royalty set on the token in basis points, whether it was given as
+    /// basis points or as a percentage
+    pub fn royalty_bps(&self) -> Option<u64> {
+        royalty_bps(self.royalty_percentage, self.royalty_basis_points)
+    }
+
+    /// True if the token sets a royalty of its own along with someone to pay it to
+    pub fn has_royalty(&self) -> bool {
+        let scheduled = matches!(
+            &self.royalty_schedule,
+            Some(schedule) if schedule.iter().any(|step| step.basis_points > 0)
+        );
+        (matches!(self.royalty_bps(), Some(bps) if bps > 0) || scheduled)
+            && (self.royalty_payment_address.is_some()
+                || matches!(&self.royalty_recipients, Some(recipients) if !recipients.is_empty()))
+    }
+}
+
+/// Basis points take precedence over a whole percentage
+pub fn royalty_bps(percentage: Option<u64>, basis_points: Option<u64>) -> Option<u64> {
+    basis_points.or_else(|| percentage.map(|percentage| percentage.saturating_mul(100)))
+}
+
+/// The rate of the last step of `schedule` that is reached,
+/// or `basis_points` while none is
+pub fn scheduled_bps(
+    basis_points: u64,
+    schedule: &[RoyaltyStep],
+    block: &BlockInfo,
+    resales: u64,
+) -> u64 {
+    schedule
+        .iter()
+        .rev()
+        .find(|step| step.threshold.is_reached(block, resales))
+        .map_or(basis_points, |step| step.basis_points)
+}
+
+/// True if the thresholds of each kind ascend through `schedule`,
+/// so no step is reached before an earlier step of the same kind
+pub fn schedule_in_order(schedule: &[RoyaltyStep]) -> bool {
+    let mut last_time = None;
+    let mut last_resales = None;
+    // None sorts before any threshold, so the first step of each kind is in order
+    schedule.iter().all(|step| match step.threshold {
+        RoyaltyThreshold::Time(time) => last_time.replace(time) < Some(time),
+        RoyaltyThreshold::Resales(count) => last_resales.replace(count) < Some(count),
+    })
+}
+
+impl Attributes for Metadata {
+    fn attributes(&self) -> Vec<(String, String)> {
+        self.attributes
+            .iter()
+            .flatten()
+            .map(|attribute| (attribute.trait_type.clone(), attribute.value.clone()))
+            .collect()
+    }
 }
 
 pub type Extension = Option<Metadata>;
@@ -43,14 +125,19 @@ pub type Extension = Option<Metadata>;
 pub type MintExtension = Option<Extension>;
 
 pub type Cw2981Contract<'a> = Cw721Contract<'a, Extension, Empty, Empty>;
-pub type ExecuteMsg = cw721_base::ExecuteMsg<Extension>;
+pub type ExecuteMsg = Cw2981ExecuteMsg;
+pub type QueryMsg = cw721_base::QueryMsg<Cw2981QueryMsg>;
 
-#[cfg(not(feature = "library"))]
+#[cfg(any(test, not(feature = "library")))]
 pub mod entry {
     use super::*;
+    use crate::execute::{
+        execute_base, update_default_royalty, update_royalty_config, validate_default_royalty,
+    };
+    use crate::state::{DEFAULT_ROYALTY, ROYALTY_TOKENS};
 
-    use cosmwasm_std::entry_point;
-    use cosmwasm_std::{Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
+    use cosmwasm_std::{entry_point, to_binary};
+    use cosmwasm_std::{Binary, Deps, DepsMut, Env, MessageInfo, Reply, Response, StdResult};
 
     #[entry_point]
     pub fn instantiate(
@@ -58,8 +145,13 @@ pub mod entry {
         env: Env,
         info: MessageInfo,
         msg: InstantiateMsg,
-    ) -> StdResult<Response> {
-        Cw2981Contract::default().instantiate(deps, env, info, msg)
+    ) -> Result<Response, ContractError> {
+        if let Some(default_royalty) = &msg.default_royalty {
+            validate_default_royalty(deps.as_ref(), default_royalty)?;
+            DEFAULT_ROYALTY.save(deps.storage, default_royalty)?;
+        }
+        ROYALTY_TOKENS.save(deps.storage, &0)?;
+        Ok(Cw2981Contract::default().instantiate(deps, env, info, msg.into())?)
     }
 
     #[entry_point]
@@ -69,28 +161,78 @@ pub mod entry {
         info: MessageInfo,
         msg: ExecuteMsg,
     ) -> Result<Response, ContractError> {
-        Cw2981Contract::default().execute(deps, env, info, msg)
+        match msg {
+            Cw2981ExecuteMsg::BaseMsg(msg) => execute_base(deps, env, info, msg),
+            Cw2981ExecuteMsg::UpdateDefaultRoyalty { default_royalty } => {
+                update_default_royalty(deps, info, default_royalty)
+            }
+            Cw2981ExecuteMsg::UpdateRoyaltyConfig { royalties_enabled } => {
+                update_royalty_config(deps, info, royalties_enabled)
+            }
+        }
     }
 
     #[entry_point]
-    pub fn query(deps: Deps, env: Env, msg: Cw2981QueryMsg) -> StdResult<Binary> {
+    pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+        Cw2981Contract::default().query_with_extension(deps, env, msg, query_extension)
+    }
+
+    fn query_extension(deps: Deps, env: Env, msg: Cw2981QueryMsg) -> StdResult<Binary> {
         match msg {
             Cw2981QueryMsg::RoyaltyInfo {
                 token_id,
                 sale_price,
-            } => to_binary(&query_royalties_info(deps, token_id, sale_price)?),
+            } => to_binary(&query_royalties_info(deps, env, token_id, sale_price)?),
+            Cw2981QueryMsg::RoyaltyInfoSplit {
+                token_id,
+                sale_price,
+            } => to_binary(&query_royalty_info_split(deps, env, token_id, sale_price)?),
             Cw2981QueryMsg::CheckRoyalties {} => to_binary(&check_royalties(deps)?),
-            _ => Cw2981Contract::default().query(deps, env, msg.into()),
+            Cw2981QueryMsg::DefaultRoyalty {} => to_binary(&query_default_royalty(deps)?),
+            Cw2981QueryMsg::RoyaltyConfig {} => to_binary(&query_royalty_config(deps)?),
+            Cw2981QueryMsg::EffectiveRoyalty { token_id } => {
+                to_binary(&query_effective_royalty(deps, env, token_id)?)
+            }
+            Cw2981QueryMsg::TokensByTrait {
+                trait_type,
+                value,
+                start_after,
+                limit,
+            } => to_binary(&Cw2981Contract::default().tokens_by_trait(
+                deps,
+                trait_type,
+                value,
+                start_after,
+                limit,
+            )?),
+            Cw2981QueryMsg::TraitCounts {
+                trait_type,
+                start_after,
+                limit,
+            } => to_binary(&Cw2981Contract::default().trait_counts(
+                deps,
+                trait_type,
+                start_after,
+                limit,
+            )?),
         }
     }
+
+    #[entry_point]
+    pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+        Ok(Cw2981Contract::default().reply(deps, env, msg)?)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::msg::{CheckRoyaltiesResponse, RoyaltiesInfoResponse};
+    use crate::msg::{
+        CheckRoyaltiesResponse, Cw2981QueryMsg, EffectiveRoyaltyResponse, RoyaltiesInfoResponse,
+        RoyaltyConfigResponse, RoyaltyInfoSplitResponse, RoyaltyPayment,
+    };
 
-    use cosmwasm_std::{from_binary, Uint128};
+    use cosmwasm_std::{from_binary, Deps, Env, Uint128};
 
     use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
     use cw721::Cw721Query;
@@ -107,6 +249,8 @@ mod tests {
             name: "SpaceShips".to_string(),
             symbol: "SPACE".to_string(),
             minter: CREATOR.to_string(),
+            soulbound: false,
+            default_royalty: None,
         };
         entry::instantiate(deps.as_mut(), mock_env(), info.clone(), init_msg).unwrap();
 
@@ -121,12 +265,70 @@ mod tests {
                 ..Metadata::default()
             }),
         };
-        let exec_msg = ExecuteMsg::Mint(mint_msg.clone());
+        let exec_msg = ExecuteMsg::BaseMsg(cw721_base::ExecuteMsg::Mint(mint_msg.clone()));
         entry::execute(deps.as_mut(), mock_env(), info, exec_msg).unwrap();
 
         let res = contract.nft_info(deps.as_ref(), token_id.into()).unwrap();
         assert_eq!(res.token_uri, mint_msg.token_uri);
         assert_eq!(res.extension, mint_msg.extension);
+
+        // the cw721 queries are answered next to the royalty ones
+        let query_msg = QueryMsg::NftInfo {
+            token_id: token_id.into(),
+        };
+        let res: cw721::NftInfoResponse<Extension> =
+            from_binary(&entry::query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(res.extension, mint_msg.extension);
+    }
+
+    #[test]
+    fn tokens_by_trait() {
+        let mut deps = mock_dependencies();
+
+        let info = mock_info(CREATOR, &[]);
+        let init_msg = InstantiateMsg {
+            name: "SpaceShips".to_string(),
+            symbol: "SPACE".to_string(),
+            minter: CREATOR.to_string(),
+            soulbound: false,
+            default_royalty: None,
+        };
+        entry::instantiate(deps.as_mut(), mock_env(), info.clone(), init_msg).unwrap();
+
+        for (token_id, class) in [("Enterprise", "Cruiser"), ("Defiant", "Escort")] {
+            let mint_msg = MintMsg {
+                token_id: token_id.to_string(),
+                owner: "john".to_string(),
+                token_uri: None,
+                extension: Some(Metadata {
+                    attributes: Some(vec![Trait {
+                        display_type: None,
+                        trait_type: "Class".to_string(),
+                        value: class.to_string(),
+                    }]),
+                    ..Metadata::default()
+                }),
+            };
+            let exec_msg = ExecuteMsg::BaseMsg(cw721_base::ExecuteMsg::Mint(mint_msg));
+            entry::execute(deps.as_mut(), mock_env(), info.clone(), exec_msg).unwrap();
+        }
+
+        let query_msg = Cw2981QueryMsg::TokensByTrait {
+            trait_type: "Class".to_string(),
+            value: "Escort".to_string(),
+            start_after: None,
+            limit: None,
+        };
+        let res: cw721::TokensResponse = from_binary(
+            &entry::query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::Extension { msg: query_msg },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(res.tokens, vec!["Defiant"]);
     }
 
     #[test]
@@ -139,6 +341,8 @@ mod tests {
             name: "SpaceShips".to_string(),
             symbol: "SPACE".to_string(),
             minter: CREATOR.to_string(),
+            soulbound: false,
+            default_royalty: None,
         };
         entry::instantiate(deps.as_mut(), mock_env(), info.clone(), init_msg).unwrap();
 
@@ -153,20 +357,96 @@ mod tests {
                 ..Metadata::default()
             }),
         };
-        let exec_msg = ExecuteMsg::Mint(mint_msg);
-        entry::execute(deps.as_mut(), mock_env(), info, exec_msg).unwrap();
+        let exec_msg = ExecuteMsg::BaseMsg(cw721_base::ExecuteMsg::Mint(mint_msg));
+        entry::execute(deps.as_mut(), mock_env(), info.clone(), exec_msg).unwrap();
 
+        // no token sets a royalty, and there is no default one
         let expected = CheckRoyaltiesResponse {
-            royalty_payments: true,
+            royalty_payments: false,
         };
         let res = check_royalties(deps.as_ref()).unwrap();
         assert_eq!(res, expected);
 
         // also check the longhand way
         let query_msg = Cw2981QueryMsg::CheckRoyalties {};
-        let query_res: CheckRoyaltiesResponse =
-            from_binary(&entry::query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        let query_res: CheckRoyaltiesResponse = from_binary(
+            &entry::query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::Extension { msg: query_msg },
+            )
+            .unwrap(),
+        )
+        .unwrap();
         assert_eq!(query_res, expected);
+
+        let mint_msg = MintMsg {
+            token_id: "Voyager".to_string(),
+            owner: "john".to_string(),
+            token_uri: None,
+            extension: Some(Metadata {
+                royalty_payment_address: Some("john".to_string()),
+                royalty_percentage: Some(5),
+                ..Metadata::default()
+            }),
+        };
+        let exec_msg = ExecuteMsg::BaseMsg(cw721_base::ExecuteMsg::Mint(mint_msg));
+        entry::execute(deps.as_mut(), mock_env(), info.clone(), exec_msg).unwrap();
+        assert!(check_royalties(deps.as_ref()).unwrap().royalty_payments);
+
+        // the minter can turn royalties off for the whole contract
+        let exec_msg = ExecuteMsg::UpdateRoyaltyConfig {
+            royalties_enabled: false,
+        };
+        let err = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("john", &[]),
+            exec_msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        entry::execute(deps.as_mut(), mock_env(), info.clone(), exec_msg).unwrap();
+
+        let query_msg = Cw2981QueryMsg::RoyaltyConfig {};
+        let query_res: RoyaltyConfigResponse = from_binary(
+            &entry::query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::Extension { msg: query_msg },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            query_res,
+            RoyaltyConfigResponse {
+                royalties_enabled: false,
+                royalty_payments: false,
+                default_royalty: None,
+            }
+        );
+        let res = query_royalties_info(
+            deps.as_ref(),
+            mock_env(),
+            "Voyager".to_string(),
+            Uint128::new(100),
+        )
+        .unwrap();
+        assert_eq!(res.royalty_amount, Uint128::zero());
+
+        let exec_msg = ExecuteMsg::UpdateRoyaltyConfig {
+            royalties_enabled: true,
+        };
+        entry::execute(deps.as_mut(), mock_env(), info.clone(), exec_msg).unwrap();
+        assert!(check_royalties(deps.as_ref()).unwrap().royalty_payments);
+
+        // burning the only token with a royalty leaves none owed
+        let exec_msg = ExecuteMsg::BaseMsg(cw721_base::ExecuteMsg::Burn {
+            token_id: "Voyager".to_string(),
+        });
+        entry::execute(deps.as_mut(), mock_env(), mock_info("john", &[]), exec_msg).unwrap();
+        assert!(!check_royalties(deps.as_ref()).unwrap().royalty_payments);
     }
 
     #[test]
@@ -178,6 +458,8 @@ mod tests {
             name: "SpaceShips".to_string(),
             symbol: "SPACE".to_string(),
             minter: CREATOR.to_string(),
+            soulbound: false,
+            default_royalty: None,
         };
         entry::instantiate(deps.as_mut(), mock_env(), info.clone(), init_msg).unwrap();
 
@@ -194,15 +476,20 @@ mod tests {
                 ..Metadata::default()
             }),
         };
-        let exec_msg = ExecuteMsg::Mint(mint_msg.clone());
+        let exec_msg = ExecuteMsg::BaseMsg(cw721_base::ExecuteMsg::Mint(mint_msg.clone()));
         entry::execute(deps.as_mut(), mock_env(), info.clone(), exec_msg).unwrap();
 
         let expected = RoyaltiesInfoResponse {
             address: mint_msg.owner,
             royalty_amount: Uint128::new(10),
         };
-        let res =
-            query_royalties_info(deps.as_ref(), token_id.to_string(), Uint128::new(100)).unwrap();
+        let res = query_royalties_info(
+            deps.as_ref(),
+            mock_env(),
+            token_id.to_string(),
+            Uint128::new(100),
+        )
+        .unwrap();
         assert_eq!(res, expected);
 
         // also check the longhand way
@@ -210,8 +497,15 @@ mod tests {
             token_id: token_id.to_string(),
             sale_price: Uint128::new(100),
         };
-        let query_res: RoyaltiesInfoResponse =
-            from_binary(&entry::query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        let query_res: RoyaltiesInfoResponse = from_binary(
+            &entry::query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::Extension { msg: query_msg },
+            )
+            .unwrap(),
+        )
+        .unwrap();
         assert_eq!(query_res, expected);
 
         // check for rounding down
@@ -229,7 +523,8 @@ mod tests {
                 ..Metadata::default()
             }),
         };
-        let voyager_exec_msg = ExecuteMsg::Mint(second_mint_msg.clone());
+        let voyager_exec_msg =
+            ExecuteMsg::BaseMsg(cw721_base::ExecuteMsg::Mint(second_mint_msg.clone()));
         entry::execute(deps.as_mut(), mock_env(), info, voyager_exec_msg).unwrap();
 
         // 43 x 0.04 (i.e., 4%) should be 1.72
@@ -241,10 +536,512 @@ mod tests {
 
         let res = query_royalties_info(
             deps.as_ref(),
+            mock_env(),
             voyager_token_id.to_string(),
             Uint128::new(43),
         )
         .unwrap();
         assert_eq!(res, voyager_expected);
     }
+
+    #[test]
+    fn default_royalty_with_token_overrides() {
+        let mut deps = mock_dependencies();
+
+        let info = mock_info(CREATOR, &[]);
+        let mut init_msg = InstantiateMsg {
+            name: "Tickets".to_string(),
+            symbol: "TIX".to_string(),
+            minter: CREATOR.to_string(),
+            soulbound: false,
+            default_royalty: Some(DefaultRoyalty {
+                percentage: 101,
+                basis_points: None,
+                payment_address: "venue".to_string(),
+                recipients: vec![],
+                schedule: vec![],
+            }),
+        };
+        let err = entry::instantiate(deps.as_mut(), mock_env(), info.clone(), init_msg.clone())
+            .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::InvalidRoyaltyPercentage { percentage: 101 }
+        );
+        init_msg.default_royalty = Some(DefaultRoyalty {
+            percentage: 5,
+            basis_points: None,
+            payment_address: "venue".to_string(),
+            recipients: vec![],
+            schedule: vec![],
+        });
+        entry::instantiate(deps.as_mut(), mock_env(), info.clone(), init_msg).unwrap();
+
+        // tokens override the percentage, the address, both or neither
+        let tokens = [
+            ("plain", None, None),
+            ("pricey", Some(10), None),
+            ("signed", None, Some("artist".to_string())),
+        ];
+        for (token_id, royalty_percentage, royalty_payment_address) in tokens {
+            let mint_msg = MintMsg {
+                token_id: token_id.to_string(),
+                owner: "john".to_string(),
+                token_uri: None,
+                extension: Some(Metadata {
+                    royalty_percentage,
+                    royalty_payment_address,
+                    ..Metadata::default()
+                }),
+            };
+            let exec_msg = ExecuteMsg::BaseMsg(cw721_base::ExecuteMsg::Mint(mint_msg));
+            entry::execute(deps.as_mut(), mock_env(), info.clone(), exec_msg).unwrap();
+        }
+
+        let royalty = |deps: Deps, token_id: &str| {
+            query_royalties_info(deps, mock_env(), token_id.to_string(), Uint128::new(100)).unwrap()
+        };
+        let expected = |address: &str, amount: u128| RoyaltiesInfoResponse {
+            address: address.to_string(),
+            royalty_amount: Uint128::new(amount),
+        };
+        assert_eq!(royalty(deps.as_ref(), "plain"), expected("venue", 5));
+        assert_eq!(royalty(deps.as_ref(), "pricey"), expected("venue", 10));
+        assert_eq!(royalty(deps.as_ref(), "signed"), expected("artist", 5));
+
+        // only the minter can update the default, and only to a valid one
+        let update_msg = ExecuteMsg::UpdateDefaultRoyalty {
+            default_royalty: Some(DefaultRoyalty {
+                percentage: 8,
+                basis_points: None,
+                payment_address: "venue".to_string(),
+                recipients: vec![],
+                schedule: vec![],
+            }),
+        };
+        let err = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("john", &[]),
+            update_msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let invalid_msg = ExecuteMsg::UpdateDefaultRoyalty {
+            default_royalty: Some(DefaultRoyalty {
+                percentage: 250,
+                basis_points: None,
+                payment_address: "venue".to_string(),
+                recipients: vec![],
+                schedule: vec![],
+            }),
+        };
+        let err = entry::execute(deps.as_mut(), mock_env(), info.clone(), invalid_msg).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::InvalidRoyaltyPercentage { percentage: 250 }
+        );
+        entry::execute(deps.as_mut(), mock_env(), info.clone(), update_msg).unwrap();
+        assert_eq!(royalty(deps.as_ref(), "plain"), expected("venue", 8));
+
+        // without a default, tokens that don't name a payment address owe nothing
+        let remove_msg = ExecuteMsg::UpdateDefaultRoyalty {
+            default_royalty: None,
+        };
+        entry::execute(deps.as_mut(), mock_env(), info, remove_msg).unwrap();
+        assert_eq!(royalty(deps.as_ref(), "pricey"), expected("", 0));
+        let query_msg = Cw2981QueryMsg::EffectiveRoyalty {
+            token_id: "signed".to_string(),
+        };
+        let res: EffectiveRoyaltyResponse = from_binary(
+            &entry::query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::Extension { msg: query_msg },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            res,
+            EffectiveRoyaltyResponse {
+                percentage: 0,
+                basis_points: 0,
+                payment_address: Some("artist".to_string()),
+                recipients: vec![RoyaltyShare {
+                    address: "artist".to_string(),
+                    weight: 1,
+                }],
+            }
+        );
+    }
+
+    #[test]
+    fn split_royalties() {
+        let mut deps = mock_dependencies();
+
+        let info = mock_info(CREATOR, &[]);
+        let share = |address: &str, weight: u64| RoyaltyShare {
+            address: address.to_string(),
+            weight,
+        };
+        let init_msg = InstantiateMsg {
+            name: "Tickets".to_string(),
+            symbol: "TIX".to_string(),
+            minter: CREATOR.to_string(),
+            soulbound: false,
+            default_royalty: Some(DefaultRoyalty {
+                percentage: 10,
+                basis_points: None,
+                payment_address: "venue".to_string(),
+                recipients: vec![share("venue", 2), share("artist", 1)],
+                schedule: vec![],
+            }),
+        };
+        entry::instantiate(deps.as_mut(), mock_env(), info.clone(), init_msg).unwrap();
+
+        for (token_id, royalty_recipients) in [
+            ("default", None),
+            ("own", Some(vec![share("artist", 1), share("promoter", 1)])),
+        ] {
+            let mint_msg = MintMsg {
+                token_id: token_id.to_string(),
+                owner: "john".to_string(),
+                token_uri: None,
+                extension: Some(Metadata {
+                    royalty_recipients,
+                    ..Metadata::default()
+                }),
+            };
+            let exec_msg = ExecuteMsg::BaseMsg(cw721_base::ExecuteMsg::Mint(mint_msg));
+            entry::execute(deps.as_mut(), mock_env(), info.clone(), exec_msg).unwrap();
+        }
+
+        // 10% of 1000 split 2:1, the dust of 100 / 3 goes to the first recipient
+        let query_msg = Cw2981QueryMsg::RoyaltyInfoSplit {
+            token_id: "default".to_string(),
+            sale_price: Uint128::new(1000),
+        };
+        let res: RoyaltyInfoSplitResponse = from_binary(
+            &entry::query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::Extension { msg: query_msg },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let payment = |address: &str, amount: u128| RoyaltyPayment {
+            address: address.to_string(),
+            amount: Uint128::new(amount),
+        };
+        assert_eq!(
+            res.payments,
+            vec![payment("venue", 67), payment("artist", 33)]
+        );
+
+        // the single address query reports the first recipient's share only
+        let res = query_royalties_info(
+            deps.as_ref(),
+            mock_env(),
+            "default".to_string(),
+            Uint128::new(1000),
+        )
+        .unwrap();
+        assert_eq!(
+            res,
+            RoyaltiesInfoResponse {
+                address: "venue".to_string(),
+                royalty_amount: Uint128::new(67),
+            }
+        );
+
+        let res = query_royalty_info_split(
+            deps.as_ref(),
+            mock_env(),
+            "own".to_string(),
+            Uint128::new(1000),
+        )
+        .unwrap();
+        assert_eq!(
+            res.payments,
+            vec![payment("artist", 50), payment("promoter", 50)]
+        );
+
+        // shares must carry weight
+        let update_msg = ExecuteMsg::UpdateDefaultRoyalty {
+            default_royalty: Some(DefaultRoyalty {
+                percentage: 10,
+                basis_points: None,
+                payment_address: "venue".to_string(),
+                recipients: vec![share("venue", 0)],
+                schedule: vec![],
+            }),
+        };
+        let err = entry::execute(deps.as_mut(), mock_env(), info.clone(), update_msg).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::InvalidRoyaltyShare {
+                address: "venue".to_string()
+            }
+        );
+
+        // nor more weight than can be added up
+        let mint_msg = MintMsg {
+            token_id: "heavy".to_string(),
+            owner: "john".to_string(),
+            token_uri: None,
+            extension: Some(Metadata {
+                royalty_recipients: Some(vec![share("artist", u64::MAX), share("promoter", 1)]),
+                ..Metadata::default()
+            }),
+        };
+        let exec_msg = ExecuteMsg::BaseMsg(cw721_base::ExecuteMsg::Mint(mint_msg));
+        let err = entry::execute(deps.as_mut(), mock_env(), info, exec_msg).unwrap_err();
+        assert_eq!(err, ContractError::RoyaltyWeightOverflow {});
+    }
+
+    #[test]
+    fn basis_point_royalties() {
+        let mut deps = mock_dependencies();
+
+        let info = mock_info(CREATOR, &[]);
+        let init_msg = InstantiateMsg {
+            name: "SpaceShips".to_string(),
+            symbol: "SPACE".to_string(),
+            minter: CREATOR.to_string(),
+            soulbound: false,
+            default_royalty: None,
+        };
+        entry::instantiate(deps.as_mut(), mock_env(), info.clone(), init_msg).unwrap();
+
+        // basis points take precedence over the percentage
+        let mint_msg = MintMsg {
+            token_id: "Defiant".to_string(),
+            owner: "sisko".to_string(),
+            token_uri: None,
+            extension: Some(Metadata {
+                royalty_payment_address: Some("sisko".to_string()),
+                royalty_percentage: Some(10),
+                royalty_basis_points: Some(250),
+                ..Metadata::default()
+            }),
+        };
+        let exec_msg = ExecuteMsg::BaseMsg(cw721_base::ExecuteMsg::Mint(mint_msg));
+        entry::execute(deps.as_mut(), mock_env(), info.clone(), exec_msg).unwrap();
+
+        let res = query_royalties_info(
+            deps.as_ref(),
+            mock_env(),
+            "Defiant".to_string(),
+            Uint128::new(1000),
+        )
+        .unwrap();
+        assert_eq!(
+            res,
+            RoyaltiesInfoResponse {
+                address: "sisko".to_string(),
+                royalty_amount: Uint128::new(25),
+            }
+        );
+
+        // out of range royalties are rejected at mint time
+        let mint_msg = MintMsg {
+            token_id: "Rio Grande".to_string(),
+            owner: "sisko".to_string(),
+            token_uri: None,
+            extension: Some(Metadata {
+                royalty_payment_address: Some("sisko".to_string()),
+                royalty_percentage: Some(250),
+                ..Metadata::default()
+            }),
+        };
+        let exec_msg = ExecuteMsg::BaseMsg(cw721_base::ExecuteMsg::Mint(mint_msg));
+        let err = entry::execute(deps.as_mut(), mock_env(), info.clone(), exec_msg).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::InvalidRoyaltyPercentage { percentage: 250 }
+        );
+
+        let mint_msg = MintMsg {
+            token_id: "Rio Grande".to_string(),
+            owner: "sisko".to_string(),
+            token_uri: None,
+            extension: Some(Metadata {
+                royalty_payment_address: Some("sisko".to_string()),
+                royalty_basis_points: Some(10_001),
+                ..Metadata::default()
+            }),
+        };
+        let exec_msg = ExecuteMsg::BaseMsg(cw721_base::ExecuteMsg::Mint(mint_msg));
+        let err = entry::execute(deps.as_mut(), mock_env(), info.clone(), exec_msg).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::InvalidRoyaltyBasisPoints {
+                basis_points: 10_001
+            }
+        );
+
+        // and when updating the default royalty
+        let exec_msg = ExecuteMsg::UpdateDefaultRoyalty {
+            default_royalty: Some(DefaultRoyalty {
+                percentage: 0,
+                basis_points: Some(20_000),
+                payment_address: "venue".to_string(),
+                recipients: vec![],
+                schedule: vec![],
+            }),
+        };
+        let err = entry::execute(deps.as_mut(), mock_env(), info, exec_msg).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::InvalidRoyaltyBasisPoints {
+                basis_points: 20_000
+            }
+        );
+    }
+
+    #[test]
+    fn royalty_schedules() {
+        let mut deps = mock_dependencies();
+        let info = mock_info(CREATOR, &[]);
+        let event = mock_env().block.time.plus_seconds(30 * 24 * 3600);
+
+        // 5% until a week before the event, 10% in the last week and 2% after it
+        let init_msg = InstantiateMsg {
+            name: "Tickets".to_string(),
+            symbol: "TIX".to_string(),
+            minter: CREATOR.to_string(),
+            soulbound: false,
+            default_royalty: Some(DefaultRoyalty {
+                percentage: 5,
+                basis_points: None,
+                payment_address: "venue".to_string(),
+                recipients: vec![],
+                schedule: vec![
+                    RoyaltyStep {
+                        threshold: RoyaltyThreshold::Time(event.minus_seconds(7 * 24 * 3600)),
+                        basis_points: 1000,
+                    },
+                    RoyaltyStep {
+                        threshold: RoyaltyThreshold::Time(event),
+                        basis_points: 200,
+                    },
+                ],
+            }),
+        };
+        entry::instantiate(deps.as_mut(), mock_env(), info.clone(), init_msg).unwrap();
+
+        // a token dropping from 10% to 1% after its second resale
+        for (token_id, schedule) in [
+            ("general", None),
+            (
+                "vip",
+                Some(vec![RoyaltyStep {
+                    threshold: RoyaltyThreshold::Resales(2),
+                    basis_points: 100,
+                }]),
+            ),
+        ] {
+            let mint_msg = MintMsg {
+                token_id: token_id.to_string(),
+                owner: "attendee".to_string(),
+                token_uri: None,
+                extension: Some(Metadata {
+                    royalty_percentage: schedule.as_ref().map(|_| 10),
+                    royalty_schedule: schedule,
+                    ..Metadata::default()
+                }),
+            };
+            let exec_msg = ExecuteMsg::BaseMsg(cw721_base::ExecuteMsg::Mint(mint_msg));
+            entry::execute(deps.as_mut(), mock_env(), info.clone(), exec_msg).unwrap();
+        }
+
+        let royalty_at = |deps: Deps, token_id: &str, env: Env| {
+            query_royalties_info(deps, env, token_id.to_string(), Uint128::new(1000))
+                .unwrap()
+                .royalty_amount
+                .u128()
+        };
+        let mut env = mock_env();
+        assert_eq!(royalty_at(deps.as_ref(), "general", env.clone()), 50);
+        env.block.time = event.minus_seconds(3600);
+        assert_eq!(royalty_at(deps.as_ref(), "general", env.clone()), 100);
+        env.block.time = event;
+        assert_eq!(royalty_at(deps.as_ref(), "general", env.clone()), 20);
+
+        // the token's own rate and schedule replace the default ones
+        assert_eq!(royalty_at(deps.as_ref(), "vip", env.clone()), 100);
+        assert_eq!(state::record_resale(&mut deps.storage, "vip").unwrap(), 1);
+        assert_eq!(royalty_at(deps.as_ref(), "vip", env.clone()), 100);
+        state::record_resale(&mut deps.storage, "vip").unwrap();
+        assert_eq!(royalty_at(deps.as_ref(), "vip", env.clone()), 10);
+
+        let query_msg = Cw2981QueryMsg::EffectiveRoyalty {
+            token_id: "vip".to_string(),
+        };
+        let res: EffectiveRoyaltyResponse = from_binary(
+            &entry::query(deps.as_ref(), env, QueryMsg::Extension { msg: query_msg }).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(res.basis_points, 100);
+
+        // steps can't go above the whole sale price either
+        let mint_msg = MintMsg {
+            token_id: "invalid".to_string(),
+            owner: "attendee".to_string(),
+            token_uri: None,
+            extension: Some(Metadata {
+                royalty_schedule: Some(vec![RoyaltyStep {
+                    threshold: RoyaltyThreshold::Resales(1),
+                    basis_points: 10_001,
+                }]),
+                ..Metadata::default()
+            }),
+        };
+        let exec_msg = ExecuteMsg::BaseMsg(cw721_base::ExecuteMsg::Mint(mint_msg));
+        let err = entry::execute(deps.as_mut(), mock_env(), info.clone(), exec_msg).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::InvalidRoyaltyBasisPoints {
+                basis_points: 10_001
+            }
+        );
+
+        // thresholds of the same kind have to ascend, different kinds can be mixed
+        let mint_msg = |token_id: &str, schedule| MintMsg {
+            token_id: token_id.to_string(),
+            owner: "attendee".to_string(),
+            token_uri: None,
+            extension: Some(Metadata {
+                royalty_schedule: Some(schedule),
+                ..Metadata::default()
+            }),
+        };
+        let unordered = vec![
+            RoyaltyStep {
+                threshold: RoyaltyThreshold::Time(event),
+                basis_points: 200,
+            },
+            RoyaltyStep {
+                threshold: RoyaltyThreshold::Resales(1),
+                basis_points: 100,
+            },
+            RoyaltyStep {
+                threshold: RoyaltyThreshold::Time(event.minus_seconds(7 * 24 * 3600)),
+                basis_points: 1000,
+            },
+        ];
+        let exec_msg = ExecuteMsg::BaseMsg(cw721_base::ExecuteMsg::Mint(mint_msg(
+            "unordered",
+            unordered.clone(),
+        )));
+        let err = entry::execute(deps.as_mut(), mock_env(), info.clone(), exec_msg).unwrap_err();
+        assert_eq!(err, ContractError::UnorderedRoyaltySchedule {});
+        let exec_msg = ExecuteMsg::BaseMsg(cw721_base::ExecuteMsg::Mint(mint_msg(
+            "mixed",
+            unordered[..2].to_vec(),
+        )));
+        entry::execute(deps.as_mut(), mock_env(), info, exec_msg).unwrap();
+    }
 }
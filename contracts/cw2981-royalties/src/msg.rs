@@ -2,8 +2,58 @@ use cosmwasm_std::Uint128;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cw721_base::msg::QueryMsg as CW721QueryMsg;
+use crate::state::{DefaultRoyalty, RoyaltyShare};
+use crate::Extension;
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct InstantiateMsg {
+    /// Name of the NFT contract
+    pub name: String,
+    /// Symbol of the NFT contract
+    pub symbol: String,
+
+    /// The minter is the only one who can create new NFTs
+    /// and update the default royalty
+    pub minter: String,
+    /// When set, tokens can't be transferred once minted
+    #[serde(default)]
+    pub soulbound: bool,
+    /// Royalty owed on tokens that don't set their own percentage or payment address
+    pub default_royalty: Option<DefaultRoyalty>,
+}
+
+impl From<InstantiateMsg> for cw721_base::InstantiateMsg {
+    fn from(msg: InstantiateMsg) -> cw721_base::InstantiateMsg {
+        cw721_base::InstantiateMsg {
+            name: msg.name,
+            symbol: msg.symbol,
+            minter: msg.minter,
+            soulbound: msg.soulbound,
+            contract_metadata: None,
+        }
+    }
+}
 
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+#[allow(clippy::large_enum_variant)]
+pub enum Cw2981ExecuteMsg {
+    BaseMsg(cw721_base::ExecuteMsg<Extension>),
+
+    /// Sets the royalty owed on tokens that don't set their own, or removes it
+    /// if unset. Can only be called by the minter
+    UpdateDefaultRoyalty {
+        default_royalty: Option<DefaultRoyalty>,
+    },
+
+    /// Turns royalties on or off for the whole contract.
+    /// Can only be called by the minter
+    UpdateRoyaltyConfig {
+        royalties_enabled: bool,
+    },
+}
+
+/// The royalty queries, sent as `QueryMsg::Extension { msg }` alongside the cw721 ones
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 #[serde(rename_all = "snake_case")]
 pub enum Cw2981QueryMsg {
@@ -11,6 +61,8 @@ pub enum Cw2981QueryMsg {
     /// by the marketplace selling the NFT, if CheckRoyalties
     /// returns true
     /// See https://eips.ethereum.org/EIPS/eip-2981
+    /// Only the first recipient's share of a split royalty is reported,
+    /// use `RoyaltyInfoSplit` to pay all of them
     RoyaltyInfo {
         token_id: String,
         // the denom of this sale must also be the denom returned by RoyaltiesInfoResponse
@@ -19,110 +71,43 @@ pub enum Cw2981QueryMsg {
         // as CW20 is just mapping of addr -> balance
         sale_price: Uint128,
     },
+    /// Same as `RoyaltyInfo`, but lists what each royalty recipient is owed.
+    /// Rounding dust goes to the first recipient
+    /// Return type: RoyaltyInfoSplitResponse
+    RoyaltyInfoSplit {
+        token_id: String,
+        sale_price: Uint128,
+    },
     /// Called against contract to determine if this NFT
     /// implements royalties. Should return a boolean as part of
-    /// CheckRoyaltiesResponse - default can simply be true
-    /// if royalties are implemented at token level
-    /// (i.e. always check on sale)
+    /// CheckRoyaltiesResponse - true while royalties are enabled
+    /// and a default royalty or any token royalty is set
     CheckRoyalties {},
-    /// Return the owner of the given token, error if token does not exist
-    /// Return type: OwnerOfResponse
-    OwnerOf {
-        token_id: String,
-        /// unset or false will filter out expired approvals, you must set to true to see them
-        include_expired: Option<bool>,
-    },
-    /// List all operators that can access all of the owner's tokens.
-    /// Return type: `OperatorsResponse`
-    AllOperators {
-        owner: String,
-        /// unset or false will filter out expired approvals, you must set to true to see them
-        include_expired: Option<bool>,
+    /// Returns the royalty settings of the whole contract, for marketplaces to cache
+    /// Return type: RoyaltyConfigResponse
+    RoyaltyConfig {},
+    /// Returns the contract level royalty, if any
+    /// Return type: DefaultRoyaltyResponse
+    DefaultRoyalty {},
+    /// Returns the royalty that applies to a token, which falls back to the
+    /// default royalty for whatever the token doesn't set itself
+    /// Return type: EffectiveRoyaltyResponse
+    EffectiveRoyalty { token_id: String },
+    /// Same as `TraitQueryMsg::TokensByTrait`, tokens are indexed by their attributes
+    /// Return type: TokensResponse
+    TokensByTrait {
+        trait_type: String,
+        value: String,
         start_after: Option<String>,
         limit: Option<u32>,
     },
-    /// Total number of tokens issued
-    NumTokens {},
-
-    /// With MetaData Extension.
-    /// Returns top-level metadata about the contract: `ContractInfoResponse`
-    ContractInfo {},
-    /// With MetaData Extension.
-    /// Returns metadata about one particular token, based on *ERC721 Metadata JSON Schema*
-    /// but directly from the contract: `NftInfoResponse`
-    NftInfo { token_id: String },
-    /// With MetaData Extension.
-    /// Returns the result of both `NftInfo` and `OwnerOf` as one query as an optimization
-    /// for clients: `AllNftInfo`
-    AllNftInfo {
-        token_id: String,
-        /// unset or false will filter out expired approvals, you must set to true to see them
-        include_expired: Option<bool>,
-    },
-
-    /// With Enumerable extension.
-    /// Returns all tokens owned by the given address, [] if unset.
-    /// Return type: TokensResponse.
-    Tokens {
-        owner: String,
+    /// Same as `TraitQueryMsg::TraitCounts`
+    /// Return type: TraitCountsResponse
+    TraitCounts {
+        trait_type: String,
         start_after: Option<String>,
         limit: Option<u32>,
     },
-    /// With Enumerable extension.
-    /// Requires pagination. Lists all token_ids controlled by the contract.
-    /// Return type: TokensResponse.
-    AllTokens {
-        start_after: Option<String>,
-        limit: Option<u32>,
-    },
-}
-
-impl From<Cw2981QueryMsg> for CW721QueryMsg {
-    fn from(msg: Cw2981QueryMsg) -> CW721QueryMsg {
-        match msg {
-            Cw2981QueryMsg::OwnerOf {
-                token_id,
-                include_expired,
-            } => CW721QueryMsg::OwnerOf {
-                token_id,
-                include_expired,
-            },
-            Cw2981QueryMsg::AllOperators {
-                owner,
-                include_expired,
-                start_after,
-                limit,
-            } => CW721QueryMsg::AllOperators {
-                owner,
-                include_expired,
-                start_after,
-                limit,
-            },
-            Cw2981QueryMsg::NumTokens {} => CW721QueryMsg::NumTokens {},
-            Cw2981QueryMsg::ContractInfo {} => CW721QueryMsg::ContractInfo {},
-            Cw2981QueryMsg::NftInfo { token_id } => CW721QueryMsg::NftInfo { token_id },
-            Cw2981QueryMsg::AllNftInfo {
-                token_id,
-                include_expired,
-            } => CW721QueryMsg::AllNftInfo {
-                token_id,
-                include_expired,
-            },
-            Cw2981QueryMsg::Tokens {
-                owner,
-                start_after,
-                limit,
-            } => CW721QueryMsg::Tokens {
-                owner,
-                start_after,
-                limit,
-            },
-            Cw2981QueryMsg::AllTokens { start_after, limit } => {
-                CW721QueryMsg::AllTokens { start_after, limit }
-            }
-            _ => panic!("cannot covert {:?} to CW721QueryMsg", msg),
-        }
-    }
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
@@ -139,3 +124,38 @@ pub struct RoyaltiesInfoResponse {
 pub struct CheckRoyaltiesResponse {
     pub royalty_payments: bool,
 }
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct RoyaltyConfigResponse {
+    pub royalties_enabled: bool,
+    /// Same as `CheckRoyalties`
+    pub royalty_payments: bool,
+    pub default_royalty: Option<DefaultRoyalty>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct DefaultRoyaltyResponse {
+    pub default_royalty: Option<DefaultRoyalty>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct EffectiveRoyaltyResponse {
+    /// The royalty in whole percents, rounded down
+    pub percentage: u64,
+    pub basis_points: u64,
+    /// No royalty is owed while this is unset
+    pub payment_address: Option<String>,
+    /// Everyone the royalty is paid to, `payment_address` alone unless split
+    pub recipients: Vec<RoyaltyShare>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct RoyaltyPayment {
+    pub address: String,
+    pub amount: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct RoyaltyInfoSplitResponse {
+    pub payments: Vec<RoyaltyPayment>,
+}
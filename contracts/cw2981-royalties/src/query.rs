@@ -1,47 +1,172 @@
-use crate::msg::{CheckRoyaltiesResponse, RoyaltiesInfoResponse};
-use crate::Cw2981Contract;
-use cosmwasm_std::{Decimal, Deps, StdResult, Uint128};
+use crate::msg::{
+    CheckRoyaltiesResponse, DefaultRoyaltyResponse, EffectiveRoyaltyResponse,
+    RoyaltiesInfoResponse, RoyaltyConfigResponse, RoyaltyInfoSplitResponse, RoyaltyPayment,
+};
+use crate::state::{RoyaltyShare, DEFAULT_ROYALTY, RESALES, ROYALTY_CONFIG, ROYALTY_TOKENS};
+use crate::{scheduled_bps, Cw2981Contract, MAX_BASIS_POINTS};
+use cosmwasm_std::{Deps, Env, StdResult, Uint128};
 
 /// NOTE: default behaviour here is to round down
 /// EIP2981 specifies that the rounding behaviour is at the discretion of the implementer
+///
+/// A split royalty reports the share of its first recipient only, the other
+/// recipients are only listed by `query_royalty_info_split`.
+/// Nothing is owed while royalties are turned off
 pub fn query_royalties_info(
     deps: Deps,
+    env: Env,
     token_id: String,
     sale_price: Uint128,
 ) -> StdResult<RoyaltiesInfoResponse> {
+    let payments = query_royalty_info_split(deps, env, token_id, sale_price)?.payments;
+
+    // without anyone to pay there is no royalty owed
+    Ok(match payments.into_iter().next() {
+        Some(payment) => RoyaltiesInfoResponse {
+            address: payment.address,
+            royalty_amount: payment.amount,
+        },
+        None => RoyaltiesInfoResponse {
+            address: String::new(),
+            royalty_amount: Uint128::zero(),
+        },
+    })
+}
+
+/// Splits the royalty between its recipients by weight, rounding down.
+/// Whatever is left over goes to the first recipient
+pub fn query_royalty_info_split(
+    deps: Deps,
+    env: Env,
+    token_id: String,
+    sale_price: Uint128,
+) -> StdResult<RoyaltyInfoSplitResponse> {
+    let royalty = query_effective_royalty(deps, env, token_id)?;
+    if !royalties_enabled(deps)? {
+        return Ok(RoyaltyInfoSplitResponse { payments: vec![] });
+    }
+    let total = royalty_amount(sale_price, royalty.basis_points);
+    // summed wide, so weights stored before they were validated can't overflow
+    let total_weight: u128 = royalty
+        .recipients
+        .iter()
+        .map(|share| u128::from(share.weight))
+        .sum();
+    if total_weight == 0 {
+        return Ok(RoyaltyInfoSplitResponse { payments: vec![] });
+    }
+
+    let mut payments: Vec<_> = royalty
+        .recipients
+        .into_iter()
+        .map(|share| RoyaltyPayment {
+            address: share.address,
+            amount: total.multiply_ratio(share.weight, total_weight),
+        })
+        .collect();
+    let paid: Uint128 = payments.iter().map(|payment| payment.amount).sum();
+    payments[0].amount += total - paid;
+
+    Ok(RoyaltyInfoSplitResponse { payments })
+}
+
+pub fn query_default_royalty(deps: Deps) -> StdResult<DefaultRoyaltyResponse> {
+    Ok(DefaultRoyaltyResponse {
+        default_royalty: DEFAULT_ROYALTY.may_load(deps.storage)?,
+    })
+}
+
+/// The percentage and recipients set on the token, each falling back
+/// to the default royalty when missing.
+/// The percentage is the one the royalty schedule sets at the current block, if any
+pub fn query_effective_royalty(
+    deps: Deps,
+    env: Env,
+    token_id: String,
+) -> StdResult<EffectiveRoyaltyResponse> {
     let contract = Cw2981Contract::default();
     let token_info = contract.tokens.load(deps.storage, &token_id)?;
+    let default_royalty = DEFAULT_ROYALTY.may_load(deps.storage)?;
+    let resales = RESALES
+        .may_load(deps.storage, &token_id)?
+        .unwrap_or_default();
 
-    let royalty_percentage = match token_info.extension {
-        Some(ref ext) => match ext.royalty_percentage {
-            Some(percentage) => Decimal::percent(percentage),
-            None => Decimal::percent(0),
-        },
-        None => Decimal::percent(0),
+    let ext = token_info.extension.unwrap_or_default();
+    let default_bps = default_royalty
+        .as_ref()
+        .map(|royalty| royalty.royalty_bps());
+    // a token with a rate of its own doesn't follow the default schedule
+    let (basis_points, schedule) = match (ext.royalty_bps(), ext.royalty_schedule) {
+        (own_bps, Some(schedule)) if !schedule.is_empty() => {
+            (own_bps.or(default_bps).unwrap_or_default(), schedule)
+        }
+        (Some(own_bps), _) => (own_bps, vec![]),
+        (None, _) => (
+            default_bps.unwrap_or_default(),
+            default_royalty
+                .as_ref()
+                .map(|royalty| royalty.schedule.clone())
+                .unwrap_or_default(),
+        ),
     };
-    let royalty_from_sale_price = sale_price * royalty_percentage;
+    let basis_points = scheduled_bps(basis_points, &schedule, &env.block, resales);
 
-    let royalty_address = match token_info.extension {
-        Some(ext) => match ext.royalty_payment_address {
-            Some(addr) => addr,
-            None => String::from(""),
+    // the token's own recipients or address take precedence over the default ones
+    let recipients = match (ext.royalty_recipients, ext.royalty_payment_address) {
+        (Some(recipients), _) if !recipients.is_empty() => recipients,
+        (_, Some(address)) => vec![RoyaltyShare { address, weight: 1 }],
+        _ => match default_royalty {
+            Some(royalty) if !royalty.recipients.is_empty() => royalty.recipients,
+            Some(royalty) => vec![RoyaltyShare {
+                address: royalty.payment_address,
+                weight: 1,
+            }],
+            None => vec![],
         },
-        None => String::from(""),
     };
+    let payment_address = recipients.first().map(|share| share.address.clone());
 
-    Ok(RoyaltiesInfoResponse {
-        address: royalty_address,
-        royalty_amount: royalty_from_sale_price,
+    Ok(EffectiveRoyaltyResponse {
+        percentage: basis_points / 100,
+        basis_points,
+        payment_address,
+        recipients,
     })
 }
 
-/// As our default implementation here specifies royalties at token level
-/// and not at contract level, it is therefore logically true that
-/// on sale, every token managed by this contract should be checked
-/// to see if royalties are owed, and to whom. If you are importing
-/// this logic, you may want a custom implementation here
-pub fn check_royalties(_deps: Deps) -> StdResult<CheckRoyaltiesResponse> {
+/// Rounds down, and never exceeds the sale price
+fn royalty_amount(sale_price: Uint128, basis_points: u64) -> Uint128 {
+    sale_price.multiply_ratio(basis_points.min(MAX_BASIS_POINTS), MAX_BASIS_POINTS)
+}
+
+/// Royalties are owed on sale while they are enabled and either the default royalty
+/// or any token sets one. Marketplaces still need to check each token, as not every
+/// token has to have a royalty
+pub fn check_royalties(deps: Deps) -> StdResult<CheckRoyaltiesResponse> {
+    let default_royalty = match DEFAULT_ROYALTY.may_load(deps.storage)? {
+        Some(royalty) => {
+            royalty.royalty_bps() > 0 || royalty.schedule.iter().any(|step| step.basis_points > 0)
+        }
+        None => false,
+    };
+    // contracts that predate the count may have tokens with a royalty
+    let token_royalties = !matches!(ROYALTY_TOKENS.may_load(deps.storage)?, Some(0));
     Ok(CheckRoyaltiesResponse {
-        royalty_payments: true,
+        royalty_payments: royalties_enabled(deps)? && (default_royalty || token_royalties),
+    })
+}
+
+pub fn query_royalty_config(deps: Deps) -> StdResult<RoyaltyConfigResponse> {
+    Ok(RoyaltyConfigResponse {
+        royalties_enabled: royalties_enabled(deps)?,
+        royalty_payments: check_royalties(deps)?.royalty_payments,
+        default_royalty: DEFAULT_ROYALTY.may_load(deps.storage)?,
     })
 }
+
+fn royalties_enabled(deps: Deps) -> StdResult<bool> {
+    Ok(ROYALTY_CONFIG
+        .may_load(deps.storage)?
+        .unwrap_or_default()
+        .royalties_enabled)
+}
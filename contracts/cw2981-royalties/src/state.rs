@@ -0,0 +1,91 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::{BlockInfo, StdResult, Storage, Timestamp};
+use cw_storage_plus::{Item, Map};
+
+use crate::royalty_bps;
+
+/// Royalty owed on every token that doesn't set its own
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct DefaultRoyalty {
+    pub percentage: u64,
+    /// The royalty in basis points, takes precedence over `percentage`
+    #[serde(default)]
+    pub basis_points: Option<u64>,
+    pub payment_address: String,
+    /// When set, the royalty is split between these instead of going to `payment_address`
+    #[serde(default)]
+    pub recipients: Vec<RoyaltyShare>,
+    /// Changes the royalty over time or as tokens are resold
+    #[serde(default)]
+    pub schedule: Vec<RoyaltyStep>,
+}
+
+impl DefaultRoyalty {
+    pub fn royalty_bps(&self) -> u64 {
+        royalty_bps(Some(self.percentage), self.basis_points).unwrap_or_default()
+    }
+}
+
+/// One recipient of a split royalty, paid in proportion to its weight
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct RoyaltyShare {
+    pub address: String,
+    pub weight: u64,
+}
+
+/// A step of a royalty schedule, the royalty is `basis_points` once its threshold is reached
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct RoyaltyStep {
+    pub threshold: RoyaltyThreshold,
+    pub basis_points: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum RoyaltyThreshold {
+    /// Reached at this block time
+    Time(Timestamp),
+    /// Reached once the token has been resold this many times
+    Resales(u64),
+}
+
+impl RoyaltyThreshold {
+    pub fn is_reached(&self, block: &BlockInfo, resales: u64) -> bool {
+        match self {
+            RoyaltyThreshold::Time(time) => block.time >= *time,
+            RoyaltyThreshold::Resales(count) => resales >= *count,
+        }
+    }
+}
+
+/// Royalty settings of the whole contract
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct RoyaltyConfig {
+    /// While false, no royalty is owed on any token
+    pub royalties_enabled: bool,
+}
+
+impl Default for RoyaltyConfig {
+    fn default() -> Self {
+        RoyaltyConfig {
+            royalties_enabled: true,
+        }
+    }
+}
+
+pub const DEFAULT_ROYALTY: Item<DefaultRoyalty> = Item::new("default_royalty");
+pub const ROYALTY_CONFIG: Item<RoyaltyConfig> = Item::new("royalty_config");
+/// How many tokens set a royalty of their own. Missing for contracts
+/// instantiated before it was tracked, in which case any token may have one
+pub const ROYALTY_TOKENS: Item<u64> = Item::new("royalty_tokens");
+/// How many times each token has been resold, kept by the contracts selling them
+pub const RESALES: Map<&str, u64> = Map::new("resales");
+
+/// Counts a resale of the token, returning how many times it has been resold
+pub fn record_resale(storage: &mut dyn Storage, token_id: &str) -> StdResult<u64> {
+    let count = RESALES.may_load(storage, token_id)?.unwrap_or_default() + 1;
+    RESALES.save(storage, token_id, &count)?;
+    Ok(count)
+}
@@ -2,12 +2,19 @@ use std::env::current_dir;
 use std::fs::create_dir_all;
 
 use cosmwasm_schema::{export_schema, export_schema_with_title, remove_schemas, schema_for};
+use cosmwasm_std::Empty;
 
 use cw721::{
-    AllNftInfoResponse, ApprovalResponse, ApprovalsResponse, ContractInfoResponse, NftInfoResponse,
-    NumTokensResponse, OperatorsResponse, OwnerOfResponse, TokensResponse,
+    AllNftInfoResponse, ApprovalResponse, ApprovalsResponse, ContractInfoResponse,
+    ContractMetadataResponse, NftInfoResponse, NumTokensResponse, OperatorsResponse,
+    OwnerOfResponse, TokensResponse,
+};
+use cw721_base::{
+    AllOwnersResponse, ApprovalsBySpenderResponse, BalanceResponse, ExecuteMsg, Extension,
+    InstantiateMsg, MinterResponse, OwnersByOperatorResponse, PauseInfoResponse, QueryMsg,
+    RestrictedAddressesResponse, RestrictedCodeIdsResponse, TraitCountsResponse,
+    TransferRestrictionResponse, TransferableResponse,
 };
-use cw721_base::{ExecuteMsg, Extension, InstantiateMsg, MinterResponse, QueryMsg};
 
 fn main() {
     let mut out_dir = current_dir().unwrap();
@@ -15,9 +22,9 @@ fn main() {
     create_dir_all(&out_dir).unwrap();
     remove_schemas(&out_dir).unwrap();
 
-    export_schema(&schema_for!(InstantiateMsg), &out_dir);
+    export_schema_with_title(&schema_for!(InstantiateMsg), &out_dir, "InstantiateMsg");
     export_schema_with_title(&schema_for!(ExecuteMsg<Extension>), &out_dir, "ExecuteMsg");
-    export_schema(&schema_for!(QueryMsg), &out_dir);
+    export_schema_with_title(&schema_for!(QueryMsg), &out_dir, "QueryMsg");
     export_schema_with_title(
         &schema_for!(AllNftInfoResponse<Extension>),
         &out_dir,
@@ -27,6 +34,11 @@ fn main() {
     export_schema(&schema_for!(ApprovalsResponse), &out_dir);
     export_schema(&schema_for!(OperatorsResponse), &out_dir);
     export_schema(&schema_for!(ContractInfoResponse), &out_dir);
+    export_schema_with_title(
+        &schema_for!(ContractMetadataResponse<Empty>),
+        &out_dir,
+        "ContractMetadataResponse",
+    );
     export_schema(&schema_for!(MinterResponse), &out_dir);
     export_schema_with_title(
         &schema_for!(NftInfoResponse<Extension>),
@@ -36,4 +48,14 @@ fn main() {
     export_schema(&schema_for!(NumTokensResponse), &out_dir);
     export_schema(&schema_for!(OwnerOfResponse), &out_dir);
     export_schema(&schema_for!(TokensResponse), &out_dir);
+    export_schema(&schema_for!(TransferableResponse), &out_dir);
+    export_schema(&schema_for!(TransferRestrictionResponse), &out_dir);
+    export_schema(&schema_for!(RestrictedAddressesResponse), &out_dir);
+    export_schema(&schema_for!(RestrictedCodeIdsResponse), &out_dir);
+    export_schema(&schema_for!(PauseInfoResponse), &out_dir);
+    export_schema(&schema_for!(BalanceResponse), &out_dir);
+    export_schema(&schema_for!(AllOwnersResponse), &out_dir);
+    export_schema(&schema_for!(TraitCountsResponse), &out_dir);
+    export_schema(&schema_for!(ApprovalsBySpenderResponse), &out_dir);
+    export_schema(&schema_for!(OwnersByOperatorResponse), &out_dir);
 }
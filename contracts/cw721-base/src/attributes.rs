@@ -0,0 +1,105 @@
+use std::collections::BTreeSet;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use cosmwasm_std::{DepsMut, Empty, Env, MessageInfo, Response, StdResult, Storage};
+
+use cw721::{CustomMsg, Cw721Execute};
+
+use crate::error::ContractError;
+use crate::msg::ExecuteMsg;
+use crate::state::Cw721Contract;
+
+/// Implemented by token extensions carrying on-chain attributes, so tokens can be
+/// looked up by trait. See `Cw721Contract::execute_indexed`
+pub trait Attributes {
+    /// Returns the (trait_type, value) pairs of the token
+    fn attributes(&self) -> Vec<(String, String)>;
+}
+
+impl<A: Attributes> Attributes for Option<A> {
+    fn attributes(&self) -> Vec<(String, String)> {
+        self.as_ref().map(A::attributes).unwrap_or_default()
+    }
+}
+
+impl<'a, T, C, M> Cw721Contract<'a, T, C, M>
+where
+    T: Serialize + DeserializeOwned + Clone + Attributes,
+    M: Serialize + DeserializeOwned + Clone,
+    C: CustomMsg,
+{
+    /// Same as `execute`, but keeps the trait index up to date on mint and burn
+    pub fn execute_indexed(
+        &self,
+        mut deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        msg: ExecuteMsg<T, M>,
+    ) -> Result<Response<C>, ContractError> {
+        match msg {
+            ExecuteMsg::Mint(msg) => {
+                let token_id = msg.token_id.clone();
+                let attributes = msg.extension.attributes();
+                let response = self.mint(deps.branch(), env, info, msg)?;
+                self.index_attributes(deps.storage, &token_id, attributes)?;
+                Ok(response)
+            }
+            ExecuteMsg::Burn { token_id } => {
+                let token = self.tokens.load(deps.storage, &token_id)?;
+                let response = self.burn(deps.branch(), env, info, token_id.clone())?;
+                self.unindex_attributes(deps.storage, &token_id, token.extension.attributes())?;
+                Ok(response)
+            }
+            msg => self.execute(deps, env, info, msg),
+        }
+    }
+
+    /// Adds a token to the trait index. When changing the extension of a token,
+    /// unindex the old attributes before indexing the new ones
+    pub fn index_attributes(
+        &self,
+        storage: &mut dyn Storage,
+        token_id: &str,
+        attributes: Vec<(String, String)>,
+    ) -> StdResult<()> {
+        // a token counts once per trait, even if listed twice
+        let attributes: BTreeSet<_> = attributes.into_iter().collect();
+        for (trait_type, value) in &attributes {
+            self.trait_tokens
+                .save(storage, (trait_type, value, token_id), &Empty {})?;
+            self.trait_counts
+                .update(storage, (trait_type, value), |count| {
+                    StdResult::Ok(count.unwrap_or_default() + 1)
+                })?;
+        }
+        Ok(())
+    }
+
+    /// Removes a token from the trait index
+    pub fn unindex_attributes(
+        &self,
+        storage: &mut dyn Storage,
+        token_id: &str,
+        attributes: Vec<(String, String)>,
+    ) -> StdResult<()> {
+        let attributes: BTreeSet<_> = attributes.into_iter().collect();
+        for (trait_type, value) in &attributes {
+            self.trait_tokens
+                .remove(storage, (trait_type, value, token_id));
+            let count = self
+                .trait_counts
+                .may_load(storage, (trait_type, value))?
+                .unwrap_or_default()
+                .saturating_sub(1);
+            if count == 0 {
+                self.trait_counts.remove(storage, (trait_type, value));
+            } else {
+                self.trait_counts
+                    .save(storage, (trait_type, value), &count)?;
+            }
+        }
+        Ok(())
+    }
+}
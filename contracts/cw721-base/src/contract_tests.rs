@@ -1,14 +1,22 @@
 #![cfg(test)]
-use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
-use cosmwasm_std::{from_binary, to_binary, CosmosMsg, DepsMut, Empty, Response, WasmMsg};
+use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info, MOCK_CONTRACT_ADDR};
+use cosmwasm_std::{
+    from_binary, to_binary, Addr, Attribute, ContractInfoResponse as WasmContractInfoResponse,
+    ContractResult, CosmosMsg, Deps, DepsMut, Empty, Env, MessageInfo, Reply, Response, StdError,
+    SubMsg, SubMsgExecutionResponse, SubMsgResult, SystemError, SystemResult, WasmMsg, WasmQuery,
+};
 
 use cw721::{
-    Approval, ApprovalResponse, ContractInfoResponse, Cw721Query, Cw721ReceiveMsg, Expiration,
-    NftInfoResponse, OperatorsResponse, OwnerOfResponse,
+    AcceptsNftsResponse, Approval, ApprovalResponse, ContractInfoResponse,
+    ContractMetadataResponse, Cw721Query, Cw721ReceiveMsg, Expiration, NftInfoResponse,
+    OperatorsResponse, OwnerOfResponse, ReceiverQueryMsg,
 };
 
+use crate::state::{TokenInfo, TransferRestriction};
 use crate::{
-    ContractError, Cw721Contract, ExecuteMsg, Extension, InstantiateMsg, MintMsg, QueryMsg,
+    BalanceResponse, ContractError, Cw721Contract, Cw721Hook, ExecuteMsg, Extension,
+    InstantiateMsg, MintMsg, MinterResponse, OperatorGrant, OwnerBalance, QueryMsg,
+    SpenderApproval, TokenAction, TransferableResponse, SAFE_SEND_REPLY_ID,
 };
 
 const MINTER: &str = "merlin";
@@ -21,6 +29,8 @@ fn setup_contract(deps: DepsMut<'_>) -> Cw721Contract<'static, Extension, Empty,
         name: CONTRACT_NAME.to_string(),
         symbol: SYMBOL.to_string(),
         minter: String::from(MINTER),
+        soulbound: false,
+        contract_metadata: None,
     };
     let info = mock_info("creator", &[]);
     let res = contract.instantiate(deps, mock_env(), info, msg).unwrap();
@@ -37,6 +47,8 @@ fn proper_instantiation() {
         name: CONTRACT_NAME.to_string(),
         symbol: SYMBOL.to_string(),
         minter: String::from(MINTER),
+        soulbound: false,
+        contract_metadata: None,
     };
     let info = mock_info("creator", &[]);
 
@@ -66,6 +78,77 @@ fn proper_instantiation() {
     assert_eq!(0, tokens.tokens.len());
 }
 
+#[test]
+fn contract_metadata() {
+    let mut deps = mock_dependencies();
+    let contract = Cw721Contract::<Extension, Empty, String>::default();
+    let query_metadata = |deps: Deps| -> ContractMetadataResponse<String> {
+        let res = contract
+            .query(deps, mock_env(), QueryMsg::ContractMetadata {})
+            .unwrap();
+        from_binary(&res).unwrap()
+    };
+
+    let msg = InstantiateMsg {
+        name: CONTRACT_NAME.to_string(),
+        symbol: SYMBOL.to_string(),
+        minter: String::from(MINTER),
+        soulbound: false,
+        contract_metadata: Some("A magic collection".to_string()),
+    };
+    contract
+        .instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg)
+        .unwrap();
+    assert_eq!(
+        query_metadata(deps.as_ref()).metadata,
+        Some("A magic collection".to_string())
+    );
+
+    // only the minter can update it
+    let update_msg = ExecuteMsg::<Extension, String>::UpdateContractMetadata(
+        "A very magic collection".to_string(),
+    );
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("random", &[]),
+            update_msg.clone(),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MINTER, &[]),
+            update_msg,
+        )
+        .unwrap();
+    assert_eq!(
+        res,
+        Response::new().add_attribute("action", "update_contract_metadata")
+    );
+    assert_eq!(
+        query_metadata(deps.as_ref()).metadata,
+        Some("A very magic collection".to_string())
+    );
+
+    // contracts instantiated without metadata have none
+    let mut deps = mock_dependencies();
+    let msg = InstantiateMsg {
+        name: CONTRACT_NAME.to_string(),
+        symbol: SYMBOL.to_string(),
+        minter: String::from(MINTER),
+        soulbound: false,
+        contract_metadata: None,
+    };
+    contract
+        .instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg)
+        .unwrap();
+    assert_eq!(query_metadata(deps.as_ref()).metadata, None);
+}
+
 #[test]
 fn minting() {
     let mut deps = mock_dependencies();
@@ -314,6 +397,611 @@ fn sending_nft() {
     );
 }
 
+#[test]
+fn safe_sending_nft() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+    // "vault" accepts tokens of this contract, "gallery" doesn't and "market" can't say
+    deps.querier.update_wasm(|query| match query {
+        WasmQuery::Smart { contract_addr, msg } if contract_addr != "market" => {
+            let ReceiverQueryMsg::AcceptsNfts { collection } = from_binary(msg).unwrap();
+            assert_eq!(collection, MOCK_CONTRACT_ADDR);
+            let res = AcceptsNftsResponse {
+                accepts: contract_addr == "vault",
+            };
+            SystemResult::Ok(ContractResult::Ok(to_binary(&res).unwrap()))
+        }
+        _ => SystemResult::Err(SystemError::UnsupportedRequest {
+            kind: "wasm".to_string(),
+        }),
+    });
+
+    let minter = mock_info(MINTER, &[]);
+    for token_id in ["melt", "freeze"] {
+        let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+            token_id: token_id.to_string(),
+            owner: String::from("venus"),
+            token_uri: None,
+            extension: None,
+        });
+        contract
+            .execute(deps.as_mut(), mock_env(), minter.clone(), mint_msg)
+            .unwrap();
+    }
+
+    let msg = to_binary("You now have the melting power").unwrap();
+    let safe_send = |contract: &str, token_id: &str, check_receiver| ExecuteMsg::SafeSendNft {
+        contract: contract.to_string(),
+        token_id: token_id.to_string(),
+        msg: msg.clone(),
+        check_receiver,
+    };
+    let reply = |result| Reply {
+        id: SAFE_SEND_REPLY_ID,
+        result,
+    };
+    let venus = mock_info("venus", &[]);
+
+    // receivers that don't answer or turn the tokens down are never sent anything
+    for target in ["gallery", "market"] {
+        let err = contract
+            .execute(
+                deps.as_mut(),
+                mock_env(),
+                venus.clone(),
+                safe_send(target, "melt", true),
+            )
+            .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::NotAReceiver {
+                contract: target.to_string()
+            }
+        );
+    }
+
+    // the receive message goes out as a submessage that is always replied to
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            venus.clone(),
+            safe_send("vault", "melt", true),
+        )
+        .unwrap();
+    let payload = Cw721ReceiveMsg {
+        sender: String::from("venus"),
+        token_id: "melt".to_string(),
+        msg: msg.clone(),
+    };
+    assert_eq!(
+        res,
+        Response::new()
+            .add_submessage(SubMsg::reply_always(
+                payload.into_cosmos_msg("vault").unwrap(),
+                SAFE_SEND_REPLY_ID
+            ))
+            .add_attribute("action", "safe_send_nft")
+            .add_attribute("sender", "venus")
+            .add_attribute("recipient", "vault")
+            .add_attribute("token_id", "melt")
+    );
+    let owner = contract
+        .owner_of(deps.as_ref(), mock_env(), "melt".to_string(), false)
+        .unwrap();
+    assert_eq!(owner.owner, "vault");
+
+    // an accepted token stays with the receiver
+    let accepted = SubMsgResult::Ok(SubMsgExecutionResponse {
+        events: vec![],
+        data: None,
+    });
+    let res = contract
+        .reply(deps.as_mut(), mock_env(), reply(accepted.clone()))
+        .unwrap();
+    assert_eq!(
+        res,
+        Response::new()
+            .add_attribute("action", "safe_send_nft_accepted")
+            .add_attribute("recipient", "vault")
+            .add_attribute("token_id", "melt")
+    );
+    assert_eq!(
+        contract.pending_sends.may_load(&deps.storage).unwrap(),
+        None
+    );
+
+    // a rejected one fails the reply, which reverts the transfer on chain
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            venus,
+            safe_send("another_contract", "freeze", false),
+        )
+        .unwrap();
+    let err = contract
+        .reply(
+            deps.as_mut(),
+            mock_env(),
+            reply(SubMsgResult::Err("too cold".to_string())),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::ReceiverRejected {
+            contract: "another_contract".to_string(),
+            token_id: "freeze".to_string(),
+            error: "too cold".to_string(),
+        }
+    );
+
+    // replies to anything else are refused
+    let err = contract
+        .reply(
+            deps.as_mut(),
+            mock_env(),
+            Reply {
+                id: 42,
+                result: accepted,
+            },
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::UnknownReplyId { id: 42 });
+}
+
+#[test]
+fn soulbound_tokens() {
+    let mut deps = mock_dependencies();
+    let contract = Cw721Contract::<Extension, Empty, Empty>::default();
+    let msg = InstantiateMsg {
+        name: CONTRACT_NAME.to_string(),
+        symbol: SYMBOL.to_string(),
+        minter: String::from(MINTER),
+        soulbound: true,
+        contract_metadata: None,
+    };
+    contract
+        .instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg)
+        .unwrap();
+
+    let mint = |token_id: &str| {
+        ExecuteMsg::Mint(MintMsg::<Extension> {
+            token_id: token_id.to_string(),
+            owner: String::from("venus"),
+            token_uri: None,
+            extension: None,
+        })
+    };
+    let minter = mock_info(MINTER, &[]);
+    contract
+        .execute(deps.as_mut(), mock_env(), minter.clone(), mint("badge"))
+        .unwrap();
+    contract
+        .execute(deps.as_mut(), mock_env(), minter.clone(), mint("pass"))
+        .unwrap();
+
+    let res: TransferableResponse = from_binary(
+        &contract
+            .query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::Transferable {
+                    token_id: "badge".to_string(),
+                },
+            )
+            .unwrap(),
+    )
+    .unwrap();
+    assert!(!res.transferable);
+
+    // the owner can't move or approve the token in any way
+    let owner = mock_info("venus", &[]);
+    let blocked = [
+        ExecuteMsg::TransferNft {
+            recipient: String::from("random"),
+            token_id: "badge".to_string(),
+        },
+        ExecuteMsg::SendNft {
+            contract: String::from("another_contract"),
+            token_id: "badge".to_string(),
+            msg: to_binary("You now have the melting power").unwrap(),
+        },
+        ExecuteMsg::Approve {
+            spender: String::from("random"),
+            token_id: "badge".to_string(),
+            expires: None,
+        },
+        ExecuteMsg::ApproveAll {
+            operator: String::from("random"),
+            expires: None,
+        },
+    ];
+    for msg in blocked {
+        let err = contract
+            .execute(deps.as_mut(), mock_env(), owner.clone(), msg)
+            .unwrap_err();
+        assert_eq!(err, ContractError::Soulbound {});
+    }
+
+    // but both the owner and the minter can burn
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            owner,
+            ExecuteMsg::Burn {
+                token_id: "badge".to_string(),
+            },
+        )
+        .unwrap();
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            minter,
+            ExecuteMsg::Burn {
+                token_id: "pass".to_string(),
+            },
+        )
+        .unwrap();
+    let count = contract.num_tokens(deps.as_ref()).unwrap();
+    assert_eq!(0, count.count);
+}
+
+/// Only lets tokens go to "venus" and tags every action it sees
+struct VenusOnly;
+
+impl Cw721Hook<Extension, Empty> for VenusOnly {
+    fn before(
+        &self,
+        _deps: Deps,
+        _env: &Env,
+        _info: &MessageInfo,
+        action: &TokenAction,
+        _token: &TokenInfo<Extension>,
+    ) -> Result<(), ContractError> {
+        match action {
+            TokenAction::Transfer { recipient, .. } if recipient != "venus" => {
+                Err(ContractError::Unauthorized {})
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn after(
+        &self,
+        _deps: DepsMut,
+        _env: &Env,
+        _info: &MessageInfo,
+        action: &TokenAction,
+        response: Response,
+    ) -> Result<Response, ContractError> {
+        Ok(response.add_attribute("hooked", action.token_id()))
+    }
+}
+
+#[test]
+fn transfer_hooks() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+    let hooks: [&dyn Cw721Hook<Extension, Empty>; 1] = [&VenusOnly];
+
+    let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+        token_id: "melt".to_string(),
+        owner: String::from("demeter"),
+        token_uri: None,
+        extension: None,
+    });
+    let res = contract
+        .execute_with_hooks(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MINTER, &[]),
+            mint_msg,
+            &hooks,
+        )
+        .unwrap();
+    // minting isn't an action on an existing token
+    assert!(!res.attributes.iter().any(|attr| attr.key == "hooked"));
+
+    let transfer = |recipient: &str| ExecuteMsg::TransferNft {
+        recipient: recipient.to_string(),
+        token_id: "melt".to_string(),
+    };
+    let owner = mock_info("demeter", &[]);
+    let err = contract
+        .execute_with_hooks(
+            deps.as_mut(),
+            mock_env(),
+            owner.clone(),
+            transfer("random"),
+            &hooks,
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let res = contract
+        .execute_with_hooks(deps.as_mut(), mock_env(), owner, transfer("venus"), &hooks)
+        .unwrap();
+    assert_eq!(
+        res.attributes.last().unwrap(),
+        &Attribute::new("hooked", "melt")
+    );
+    let owner = contract
+        .owner_of(deps.as_ref(), mock_env(), "melt".to_string(), false)
+        .unwrap();
+    assert_eq!(owner.owner, "venus");
+}
+
+#[test]
+fn restricted_recipients() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+    // "market" is an instance of code id 7, everything else is a plain account
+    deps.querier.update_wasm(|query| match query {
+        WasmQuery::ContractInfo { contract_addr } if contract_addr == "market" => SystemResult::Ok(
+            ContractResult::Ok(to_binary(&WasmContractInfoResponse::new(7, "creator")).unwrap()),
+        ),
+        _ => SystemResult::Err(SystemError::UnsupportedRequest {
+            kind: "wasm".to_string(),
+        }),
+    });
+
+    let minter = mock_info(MINTER, &[]);
+    let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+        token_id: "melt".to_string(),
+        owner: String::from("venus"),
+        token_uri: None,
+        extension: None,
+    });
+    contract
+        .execute(deps.as_mut(), mock_env(), minter.clone(), mint_msg)
+        .unwrap();
+
+    // only the minter manages the restriction
+    let allowlist = ExecuteMsg::SetTransferRestriction {
+        restriction: Some(TransferRestriction::Allowlist),
+    };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("venus", &[]),
+            allowlist.clone(),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    contract
+        .execute(deps.as_mut(), mock_env(), minter.clone(), allowlist)
+        .unwrap();
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            minter.clone(),
+            ExecuteMsg::UpdateRestrictedAddresses {
+                add: vec![String::from("demeter"), String::from("ceres")],
+                remove: vec![],
+            },
+        )
+        .unwrap();
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            minter.clone(),
+            ExecuteMsg::UpdateRestrictedCodeIds {
+                add: vec![7],
+                remove: vec![],
+            },
+        )
+        .unwrap();
+
+    let res = contract
+        .restricted_addresses(deps.as_ref(), None, Some(1))
+        .unwrap();
+    assert_eq!(res.addresses, vec![String::from("ceres")]);
+    let res = contract
+        .restricted_addresses(deps.as_ref(), Some(String::from("ceres")), None)
+        .unwrap();
+    assert_eq!(res.addresses, vec![String::from("demeter")]);
+    let res = contract
+        .restricted_code_ids(deps.as_ref(), None, None)
+        .unwrap();
+    assert_eq!(res.code_ids, vec![7]);
+
+    let transfer = |recipient: &str| ExecuteMsg::TransferNft {
+        recipient: recipient.to_string(),
+        token_id: "melt".to_string(),
+    };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("venus", &[]),
+            transfer("random"),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::RecipientNotAllowed {
+            recipient: String::from("random")
+        }
+    );
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("venus", &[]),
+            transfer("demeter"),
+        )
+        .unwrap();
+    // contracts are matched by code id
+    let send = ExecuteMsg::SendNft {
+        contract: String::from("market"),
+        token_id: "melt".to_string(),
+        msg: to_binary("list it").unwrap(),
+    };
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info("demeter", &[]), send)
+        .unwrap();
+
+    // as a denylist the same entries are now blocked
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            minter,
+            ExecuteMsg::SetTransferRestriction {
+                restriction: Some(TransferRestriction::Denylist),
+            },
+        )
+        .unwrap();
+    let res = contract.transfer_restriction(deps.as_ref()).unwrap();
+    assert_eq!(res.restriction, Some(TransferRestriction::Denylist));
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("market", &[]),
+            transfer("ceres"),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::RecipientNotAllowed {
+            recipient: String::from("ceres")
+        }
+    );
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("market", &[]),
+            transfer("random"),
+        )
+        .unwrap();
+}
+
+#[test]
+fn pausing() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+    let minter = mock_info(MINTER, &[]);
+    let pauser = mock_info("guardian", &[]);
+    let owner = mock_info("venus", &[]);
+
+    let mint = |token_id: &str| {
+        ExecuteMsg::Mint(MintMsg::<Extension> {
+            token_id: token_id.to_string(),
+            owner: String::from("venus"),
+            token_uri: None,
+            extension: None,
+        })
+    };
+    contract
+        .execute(deps.as_mut(), mock_env(), minter.clone(), mint("melt"))
+        .unwrap();
+
+    // only the minter or the pauser can pause
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            pauser.clone(),
+            ExecuteMsg::Pause {},
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            minter.clone(),
+            ExecuteMsg::SetPauser {
+                pauser: Some(String::from("guardian")),
+            },
+        )
+        .unwrap();
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            pauser.clone(),
+            ExecuteMsg::Pause {},
+        )
+        .unwrap();
+
+    let info = contract.pause_info(deps.as_ref()).unwrap();
+    assert_eq!(info.pauser, Some(String::from("guardian")));
+    let paused = info.paused.unwrap();
+    assert_eq!(paused.paused_by, "guardian");
+    assert_eq!(paused.paused_at, mock_env().block.time);
+
+    let blocked = [
+        (minter.clone(), mint("grow")),
+        (
+            owner.clone(),
+            ExecuteMsg::TransferNft {
+                recipient: String::from("random"),
+                token_id: "melt".to_string(),
+            },
+        ),
+        (
+            owner.clone(),
+            ExecuteMsg::Approve {
+                spender: String::from("random"),
+                token_id: "melt".to_string(),
+                expires: None,
+            },
+        ),
+        (
+            owner.clone(),
+            ExecuteMsg::ApproveAll {
+                operator: String::from("random"),
+                expires: None,
+            },
+        ),
+        (
+            owner.clone(),
+            ExecuteMsg::Burn {
+                token_id: "melt".to_string(),
+            },
+        ),
+    ];
+    for (info, msg) in blocked {
+        let err = contract
+            .execute(deps.as_mut(), mock_env(), info, msg)
+            .unwrap_err();
+        assert_eq!(err, ContractError::Paused {});
+    }
+    // queries keep working
+    let res = contract
+        .owner_of(deps.as_ref(), mock_env(), "melt".to_string(), false)
+        .unwrap();
+    assert_eq!(res.owner, "venus");
+
+    contract
+        .execute(deps.as_mut(), mock_env(), minter, ExecuteMsg::Unpause {})
+        .unwrap();
+    let err = contract
+        .execute(deps.as_mut(), mock_env(), pauser, ExecuteMsg::Unpause {})
+        .unwrap_err();
+    assert_eq!(err, ContractError::NotPaused {});
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            owner,
+            ExecuteMsg::TransferNft {
+                recipient: String::from("random"),
+                token_id: "melt".to_string(),
+            },
+        )
+        .unwrap();
+}
+
 #[test]
 fn approving_revoking() {
     let mut deps = mock_dependencies();
@@ -673,6 +1361,417 @@ fn approving_all_revoking_all() {
     assert_eq!(0, res.operators.len());
 }
 
+#[test]
+fn approvals_by_spender_and_owners_by_operator() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+
+    let minter = mock_info(MINTER, &[]);
+    for token_id in ["grow", "shrink", "sleep"] {
+        let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+            token_id: token_id.to_string(),
+            owner: String::from("demeter"),
+            token_uri: None,
+            extension: None,
+        });
+        contract
+            .execute(deps.as_mut(), mock_env(), minter.clone(), mint_msg)
+            .unwrap();
+    }
+
+    let owner = mock_info("demeter", &[]);
+    let expires = Expiration::AtHeight(1234567);
+    for (token_id, expires) in [("grow", None), ("shrink", Some(expires)), ("sleep", None)] {
+        let approve_msg = ExecuteMsg::Approve {
+            spender: String::from("random"),
+            token_id: token_id.to_string(),
+            expires,
+        };
+        contract
+            .execute(deps.as_mut(), mock_env(), owner.clone(), approve_msg)
+            .unwrap();
+    }
+
+    // paging through the approvals of a spender
+    let res = contract
+        .approvals_by_spender(
+            deps.as_ref(),
+            mock_env(),
+            String::from("random"),
+            false,
+            None,
+            Some(2),
+        )
+        .unwrap();
+    assert_eq!(
+        res.approvals,
+        vec![
+            SpenderApproval {
+                token_id: String::from("grow"),
+                expires: Expiration::Never {},
+            },
+            SpenderApproval {
+                token_id: String::from("shrink"),
+                expires,
+            },
+        ]
+    );
+    let res = contract
+        .approvals_by_spender(
+            deps.as_ref(),
+            mock_env(),
+            String::from("random"),
+            false,
+            Some(String::from("shrink")),
+            Some(2),
+        )
+        .unwrap();
+    assert_eq!(res.approvals.len(), 1);
+    assert_eq!(res.approvals[0].token_id, "sleep");
+
+    // expired approvals are only shown on request
+    let mut late_env = mock_env();
+    late_env.block.height = 1234568;
+    let res = contract
+        .approvals_by_spender(
+            deps.as_ref(),
+            late_env.clone(),
+            String::from("random"),
+            false,
+            None,
+            None,
+        )
+        .unwrap();
+    assert_eq!(res.approvals.len(), 2);
+    let res = contract
+        .approvals_by_spender(
+            deps.as_ref(),
+            late_env,
+            String::from("random"),
+            true,
+            None,
+            None,
+        )
+        .unwrap();
+    assert_eq!(res.approvals.len(), 3);
+
+    // revoking, transferring and burning all drop the approval from the index
+    let revoke_msg = ExecuteMsg::Revoke {
+        spender: String::from("random"),
+        token_id: String::from("grow"),
+    };
+    contract
+        .execute(deps.as_mut(), mock_env(), owner.clone(), revoke_msg)
+        .unwrap();
+    let transfer_msg = ExecuteMsg::TransferNft {
+        recipient: String::from("hermes"),
+        token_id: String::from("shrink"),
+    };
+    contract
+        .execute(deps.as_mut(), mock_env(), owner.clone(), transfer_msg)
+        .unwrap();
+    let burn_msg = ExecuteMsg::Burn {
+        token_id: String::from("sleep"),
+    };
+    contract
+        .execute(deps.as_mut(), mock_env(), owner.clone(), burn_msg)
+        .unwrap();
+    let res = contract
+        .approvals_by_spender(
+            deps.as_ref(),
+            mock_env(),
+            String::from("random"),
+            true,
+            None,
+            None,
+        )
+        .unwrap();
+    assert_eq!(res.approvals, vec![]);
+
+    // operators are indexed by the owners granting them
+    for granter in ["demeter", "hermes", "apollo"] {
+        let approve_all_msg = ExecuteMsg::ApproveAll {
+            operator: String::from("buddy"),
+            expires: None,
+        };
+        contract
+            .execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info(granter, &[]),
+                approve_all_msg,
+            )
+            .unwrap();
+    }
+    let res = contract
+        .owners_by_operator(
+            deps.as_ref(),
+            mock_env(),
+            String::from("buddy"),
+            false,
+            None,
+            Some(2),
+        )
+        .unwrap();
+    assert_eq!(
+        res.owners,
+        vec![
+            OperatorGrant {
+                owner: String::from("apollo"),
+                expires: Expiration::Never {},
+            },
+            OperatorGrant {
+                owner: String::from("demeter"),
+                expires: Expiration::Never {},
+            },
+        ]
+    );
+
+    let revoke_all_msg = ExecuteMsg::RevokeAll {
+        operator: String::from("buddy"),
+    };
+    contract
+        .execute(deps.as_mut(), mock_env(), owner, revoke_all_msg)
+        .unwrap();
+    let res = contract
+        .owners_by_operator(
+            deps.as_ref(),
+            mock_env(),
+            String::from("buddy"),
+            false,
+            Some(String::from("apollo")),
+            None,
+        )
+        .unwrap();
+    assert_eq!(res.owners.len(), 1);
+    assert_eq!(res.owners[0].owner, "hermes");
+}
+
+#[test]
+fn pruning_expired() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+
+    let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+        token_id: String::from("grow"),
+        owner: String::from("demeter"),
+        token_uri: None,
+        extension: None,
+    });
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), mint_msg)
+        .unwrap();
+
+    let owner = mock_info("demeter", &[]);
+    let expires = Expiration::AtHeight(1234567);
+    let approve_msg = ExecuteMsg::Approve {
+        spender: String::from("random"),
+        token_id: String::from("grow"),
+        expires: Some(expires),
+    };
+    contract
+        .execute(deps.as_mut(), mock_env(), owner.clone(), approve_msg)
+        .unwrap();
+    for operator in ["apollo", "buddy", "hermes"] {
+        let approve_all_msg = ExecuteMsg::ApproveAll {
+            operator: String::from(operator),
+            expires: Some(expires),
+        };
+        contract
+            .execute(deps.as_mut(), mock_env(), owner.clone(), approve_all_msg)
+            .unwrap();
+    }
+    let approve_all_msg = ExecuteMsg::ApproveAll {
+        operator: String::from("zeus"),
+        expires: None,
+    };
+    contract
+        .execute(deps.as_mut(), mock_env(), owner.clone(), approve_all_msg)
+        .unwrap();
+
+    let mut late_env = mock_env();
+    late_env.block.height = 1234568;
+
+    // touching the token drops its expired approvals
+    let approve_msg = ExecuteMsg::Approve {
+        spender: String::from("buddy"),
+        token_id: String::from("grow"),
+        expires: None,
+    };
+    contract
+        .execute(deps.as_mut(), late_env.clone(), owner, approve_msg)
+        .unwrap();
+    let token = contract.tokens.load(&deps.storage, "grow").unwrap();
+    assert_eq!(
+        token.approvals,
+        vec![crate::state::Approval {
+            spender: Addr::unchecked("buddy"),
+            expires: Expiration::Never {},
+        }]
+    );
+    let res = contract
+        .approvals_by_spender(
+            deps.as_ref(),
+            late_env.clone(),
+            String::from("random"),
+            true,
+            None,
+            None,
+        )
+        .unwrap();
+    assert_eq!(res.approvals, vec![]);
+
+    // anyone can prune expired operators, one page of grants at a time
+    let prune_msg = |start_after: Option<&str>| ExecuteMsg::PruneExpired {
+        owner: String::from("demeter"),
+        start_after: start_after.map(String::from),
+        limit: Some(2),
+    };
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            late_env.clone(),
+            mock_info("random", &[]),
+            prune_msg(None),
+        )
+        .unwrap();
+    assert_eq!(
+        res,
+        Response::new()
+            .add_attribute("action", "prune_expired")
+            .add_attribute("owner", "demeter")
+            .add_attribute("pruned", "2")
+            .add_attribute("last_scanned", "buddy")
+    );
+    // the unexpired grant counts towards the limit as well
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            late_env.clone(),
+            mock_info("random", &[]),
+            prune_msg(Some("buddy")),
+        )
+        .unwrap();
+    assert_eq!(res.attributes[2], Attribute::new("pruned", "1"));
+    assert_eq!(res.attributes[3], Attribute::new("last_scanned", "zeus"));
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            late_env.clone(),
+            mock_info("random", &[]),
+            prune_msg(Some("zeus")),
+        )
+        .unwrap();
+    assert_eq!(res.attributes[2], Attribute::new("pruned", "0"));
+    assert_eq!(res.attributes.len(), 3);
+
+    let res = contract
+        .operators(
+            deps.as_ref(),
+            late_env.clone(),
+            String::from("demeter"),
+            true,
+            None,
+            None,
+        )
+        .unwrap();
+    assert_eq!(
+        res.operators,
+        vec![cw721::Approval {
+            spender: String::from("zeus"),
+            expires: Expiration::Never {},
+        }]
+    );
+    let res = contract
+        .owners_by_operator(
+            deps.as_ref(),
+            late_env,
+            String::from("buddy"),
+            true,
+            None,
+            None,
+        )
+        .unwrap();
+    assert_eq!(res.owners, vec![]);
+}
+
+#[test]
+fn owner_balances() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+
+    let minter = mock_info(MINTER, &[]);
+    for (token_id, owner) in [
+        ("grow", "demeter"),
+        ("sing", "apollo"),
+        ("sleep", "demeter"),
+    ] {
+        let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+            token_id: String::from(token_id),
+            owner: String::from(owner),
+            token_uri: None,
+            extension: None,
+        });
+        contract
+            .execute(deps.as_mut(), mock_env(), minter.clone(), mint_msg)
+            .unwrap();
+    }
+
+    let res = contract
+        .balance(deps.as_ref(), String::from("demeter"))
+        .unwrap();
+    assert_eq!(res, BalanceResponse { balance: 2 });
+    let res = contract
+        .balance(deps.as_ref(), String::from("hermes"))
+        .unwrap();
+    assert_eq!(res, BalanceResponse { balance: 0 });
+
+    // transfers and burns keep the balances in sync
+    let transfer_msg = ExecuteMsg::TransferNft {
+        recipient: String::from("hermes"),
+        token_id: String::from("sleep"),
+    };
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("demeter", &[]),
+            transfer_msg,
+        )
+        .unwrap();
+    let burn_msg = ExecuteMsg::Burn {
+        token_id: String::from("sing"),
+    };
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("apollo", &[]),
+            burn_msg,
+        )
+        .unwrap();
+
+    // owners without tokens are no longer listed
+    let res = contract.all_owners(deps.as_ref(), None, Some(1)).unwrap();
+    assert_eq!(
+        res.owners,
+        vec![OwnerBalance {
+            owner: String::from("demeter"),
+            balance: 1,
+        }]
+    );
+    let res = contract
+        .all_owners(deps.as_ref(), Some(String::from("demeter")), None)
+        .unwrap();
+    assert_eq!(
+        res.owners,
+        vec![OwnerBalance {
+            owner: String::from("hermes"),
+            balance: 1,
+        }]
+    );
+}
+
 #[test]
 fn query_tokens_by_owner() {
     let mut deps = mock_dependencies();
@@ -749,3 +1848,99 @@ fn query_tokens_by_owner() {
         .unwrap();
     assert_eq!(&by_demeter[1..], &tokens.tokens[..]);
 }
+
+#[test]
+fn query_tokens_with_info() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+    let minter = mock_info(MINTER, &[]);
+
+    for (token_id, owner) in [
+        ("grow1", "demeter"),
+        ("grow2", "ceres"),
+        ("sing", "demeter"),
+    ] {
+        let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+            token_id: String::from(token_id),
+            owner: String::from(owner),
+            token_uri: Some(format!("https://example.com/{}.json", token_id)),
+            extension: None,
+        });
+        contract
+            .execute(deps.as_mut(), mock_env(), minter.clone(), mint_msg)
+            .unwrap();
+    }
+
+    // every page entry matches the AllNftInfo of that token
+    let res = contract
+        .all_tokens_with_info(deps.as_ref(), mock_env(), None, Some(2))
+        .unwrap();
+    let ids: Vec<_> = res.tokens.iter().map(|(id, _)| id.as_str()).collect();
+    assert_eq!(ids, vec!["grow1", "grow2"]);
+    for (token_id, info) in res.tokens {
+        let expected = contract
+            .all_nft_info(deps.as_ref(), mock_env(), token_id, false)
+            .unwrap();
+        assert_eq!(info, expected);
+    }
+    let res = contract
+        .all_tokens_with_info(deps.as_ref(), mock_env(), Some(String::from("grow2")), None)
+        .unwrap();
+    assert_eq!(res.tokens.len(), 1);
+    assert_eq!(res.tokens[0].0, "sing");
+
+    // by owner
+    let res = contract
+        .tokens_with_info(
+            deps.as_ref(),
+            mock_env(),
+            String::from("demeter"),
+            Some(String::from("grow1")),
+            None,
+        )
+        .unwrap();
+    assert_eq!(res.tokens.len(), 1);
+    let (token_id, info) = &res.tokens[0];
+    assert_eq!(token_id, "sing");
+    assert_eq!(info.access.owner, "demeter");
+    assert_eq!(
+        info.info.token_uri,
+        Some(String::from("https://example.com/sing.json"))
+    );
+}
+
+#[test]
+fn extension_queries() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+
+    // plain contracts have no extension queries
+    let err = contract
+        .query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Extension { msg: Empty {} },
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("Extension queries are not supported")
+    );
+
+    // extending contracts answer theirs next to the cw721 ones
+    let query = |msg: QueryMsg<String>| {
+        contract.query_with_extension(deps.as_ref(), mock_env(), msg, |_, _, msg| {
+            to_binary(&msg.to_uppercase())
+        })
+    };
+    let res: String = from_binary(
+        &query(QueryMsg::Extension {
+            msg: "shout".to_string(),
+        })
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res, "SHOUT");
+    let res: MinterResponse = from_binary(&query(QueryMsg::Minter {}).unwrap()).unwrap();
+    assert_eq!(res.minter, MINTER);
+}
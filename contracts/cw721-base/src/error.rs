@@ -17,4 +17,29 @@ pub enum ContractError {
 
     #[error("Approval not found for: {spender}")]
     ApprovalNotFound { spender: String },
+
+    #[error("Token is soulbound and cannot be transferred or approved")]
+    Soulbound {},
+
+    #[error("{recipient} is not allowed to receive tokens")]
+    RecipientNotAllowed { recipient: String },
+
+    #[error("Contract is paused")]
+    Paused {},
+
+    #[error("Contract is not paused")]
+    NotPaused {},
+
+    #[error("{contract} rejected token {token_id}: {error}")]
+    ReceiverRejected {
+        contract: String,
+        token_id: String,
+        error: String,
+    },
+
+    #[error("{contract} does not accept tokens of this contract")]
+    NotAReceiver { contract: String },
+
+    #[error("Unknown reply id: {id}")]
+    UnknownReplyId { id: u64 },
 }
@@ -1,19 +1,33 @@
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 
-use cosmwasm_std::{Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
+use cosmwasm_std::{
+    Addr, Binary, ContractInfoResponse as WasmContractInfoResponse, Deps, DepsMut, Empty, Env,
+    MessageInfo, Order, Reply, Response, StdResult, Storage, SubMsg, SubMsgResult, WasmQuery,
+};
 
 use cw2::set_contract_version;
-use cw721::{ContractInfoResponse, CustomMsg, Cw721Execute, Cw721ReceiveMsg, Expiration};
+use cw721::{
+    AcceptsNftsResponse, ContractInfoResponse, CustomMsg, Cw721Execute, Cw721ReceiveMsg,
+    Expiration, ReceiverQueryMsg,
+};
+use cw_storage_plus::Bound;
+use cw_utils::maybe_addr;
 
 use crate::error::ContractError;
 use crate::msg::{ExecuteMsg, InstantiateMsg, MintMsg};
-use crate::state::{Approval, Cw721Contract, TokenInfo};
+use crate::query::{DEFAULT_LIMIT, MAX_LIMIT};
+use crate::state::{
+    Approval, Cw721Contract, PauseInfo, PendingSend, TokenInfo, TransferRestriction,
+};
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:cw721-base";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+/// Reply id of the receive message dispatched by `SafeSendNft`
+pub const SAFE_SEND_REPLY_ID: u64 = 1;
+
 impl<'a, T, C, M> Cw721Contract<'a, T, C, M>
 where
     T: Serialize + DeserializeOwned + Clone,
@@ -25,7 +39,7 @@ where
         deps: DepsMut,
         _env: Env,
         _info: MessageInfo,
-        msg: InstantiateMsg,
+        msg: InstantiateMsg<M>,
     ) -> StdResult<Response<C>> {
         set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
@@ -36,6 +50,10 @@ where
         self.contract_info.save(deps.storage, &info)?;
         let minter = deps.api.addr_validate(&msg.minter)?;
         self.minter.save(deps.storage, &minter)?;
+        self.soulbound.save(deps.storage, &msg.soulbound)?;
+        if let Some(metadata) = msg.contract_metadata {
+            self.contract_metadata.save(deps.storage, &metadata)?;
+        }
         Ok(Response::default())
     }
 
@@ -44,7 +62,7 @@ where
         deps: DepsMut,
         env: Env,
         info: MessageInfo,
-        msg: ExecuteMsg<T>,
+        msg: ExecuteMsg<T, M>,
     ) -> Result<Response<C>, ContractError> {
         match msg {
             ExecuteMsg::Mint(msg) => self.mint(deps, env, info, msg),
@@ -69,7 +87,70 @@ where
                 token_id,
                 msg,
             } => self.send_nft(deps, env, info, contract, token_id, msg),
+            ExecuteMsg::SafeSendNft {
+                contract,
+                token_id,
+                msg,
+                check_receiver,
+            } => self.safe_send_nft(deps, env, info, contract, token_id, msg, check_receiver),
             ExecuteMsg::Burn { token_id } => self.burn(deps, env, info, token_id),
+            ExecuteMsg::SetTransferRestriction { restriction } => {
+                self.set_transfer_restriction(deps, info, restriction)
+            }
+            ExecuteMsg::UpdateRestrictedAddresses { add, remove } => {
+                self.update_restricted_addresses(deps, info, add, remove)
+            }
+            ExecuteMsg::UpdateRestrictedCodeIds { add, remove } => {
+                self.update_restricted_code_ids(deps, info, add, remove)
+            }
+            ExecuteMsg::Pause {} => self.pause(deps, env, info),
+            ExecuteMsg::Unpause {} => self.unpause(deps, info),
+            ExecuteMsg::SetPauser { pauser } => self.set_pauser(deps, info, pauser),
+            ExecuteMsg::PruneExpired {
+                owner,
+                start_after,
+                limit,
+            } => self.prune_expired(deps, env, owner, start_after, limit),
+            ExecuteMsg::UpdateContractMetadata(metadata) => {
+                self.update_contract_metadata(deps, info, metadata)
+            }
+        }
+    }
+
+    /// Handles the receiver's reply to a `SafeSendNft`. An error reverts the transfer
+    pub fn reply(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        msg: Reply,
+    ) -> Result<Response<C>, ContractError> {
+        if msg.id != SAFE_SEND_REPLY_ID {
+            return Err(ContractError::UnknownReplyId { id: msg.id });
+        }
+
+        let mut pending = self
+            .pending_sends
+            .may_load(deps.storage)?
+            .unwrap_or_default();
+        let send = pending
+            .pop()
+            .ok_or(ContractError::UnknownReplyId { id: msg.id })?;
+        if pending.is_empty() {
+            self.pending_sends.remove(deps.storage);
+        } else {
+            self.pending_sends.save(deps.storage, &pending)?;
+        }
+
+        match msg.result {
+            SubMsgResult::Ok(_) => Ok(Response::new()
+                .add_attribute("action", "safe_send_nft_accepted")
+                .add_attribute("recipient", send.contract)
+                .add_attribute("token_id", send.token_id)),
+            SubMsgResult::Err(error) => Err(ContractError::ReceiverRejected {
+                contract: send.contract.into_string(),
+                token_id: send.token_id,
+                error,
+            }),
         }
     }
 }
@@ -88,6 +169,7 @@ where
         info: MessageInfo,
         msg: MintMsg<T>,
     ) -> Result<Response<C>, ContractError> {
+        self.check_not_paused(deps.storage)?;
         let minter = self.minter.load(deps.storage)?;
 
         if info.sender != minter {
@@ -95,8 +177,9 @@ where
         }
 
         // create the token
+        let owner = deps.api.addr_validate(&msg.owner)?;
         let token = TokenInfo {
-            owner: deps.api.addr_validate(&msg.owner)?,
+            owner: owner.clone(),
             approvals: vec![],
             token_uri: msg.token_uri,
             extension: msg.extension,
@@ -108,6 +191,7 @@ where
             })?;
 
         self.increment_tokens(deps.storage)?;
+        self.increment_balance(deps.storage, &owner)?;
 
         Ok(Response::new()
             .add_attribute("action", "mint")
@@ -115,6 +199,238 @@ where
             .add_attribute("owner", msg.owner)
             .add_attribute("token_id", msg.token_id))
     }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn safe_send_nft(
+        &self,
+        mut deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        contract: String,
+        token_id: String,
+        msg: Binary,
+        check_receiver: bool,
+    ) -> Result<Response<C>, ContractError> {
+        if check_receiver {
+            let query = ReceiverQueryMsg::AcceptsNfts {
+                collection: env.contract.address.to_string(),
+            };
+            let accepts = deps
+                .querier
+                .query_wasm_smart::<AcceptsNftsResponse>(&contract, &query)
+                .map(|res| res.accepts)
+                .unwrap_or(false);
+            if !accepts {
+                return Err(ContractError::NotAReceiver { contract });
+            }
+        }
+
+        // Transfer token, the reply rolls it back if the receiver fails
+        let contract = deps.api.addr_validate(&contract)?;
+        self._transfer_nft(deps.branch(), &env, &info, contract.as_str(), &token_id)?;
+
+        let mut pending = self
+            .pending_sends
+            .may_load(deps.storage)?
+            .unwrap_or_default();
+        pending.push(PendingSend {
+            contract: contract.clone(),
+            token_id: token_id.clone(),
+        });
+        self.pending_sends.save(deps.storage, &pending)?;
+
+        let send = Cw721ReceiveMsg {
+            sender: info.sender.to_string(),
+            token_id: token_id.clone(),
+            msg,
+        };
+
+        Ok(Response::new()
+            .add_submessage(SubMsg::reply_always(
+                send.into_cosmos_msg(contract.clone())?,
+                SAFE_SEND_REPLY_ID,
+            ))
+            .add_attribute("action", "safe_send_nft")
+            .add_attribute("sender", info.sender)
+            .add_attribute("recipient", contract)
+            .add_attribute("token_id", token_id))
+    }
+
+    pub fn update_contract_metadata(
+        &self,
+        deps: DepsMut,
+        info: MessageInfo,
+        metadata: M,
+    ) -> Result<Response<C>, ContractError> {
+        self.check_minter(deps.as_ref(), &info)?;
+        self.contract_metadata.save(deps.storage, &metadata)?;
+
+        Ok(Response::new().add_attribute("action", "update_contract_metadata"))
+    }
+
+    pub fn set_transfer_restriction(
+        &self,
+        deps: DepsMut,
+        info: MessageInfo,
+        restriction: Option<TransferRestriction>,
+    ) -> Result<Response<C>, ContractError> {
+        self.check_minter(deps.as_ref(), &info)?;
+
+        let mode = match restriction {
+            Some(restriction) => {
+                self.transfer_restriction.save(deps.storage, &restriction)?;
+                match restriction {
+                    TransferRestriction::Allowlist => "allowlist",
+                    TransferRestriction::Denylist => "denylist",
+                }
+            }
+            None => {
+                self.transfer_restriction.remove(deps.storage);
+                "none"
+            }
+        };
+
+        Ok(Response::new()
+            .add_attribute("action", "set_transfer_restriction")
+            .add_attribute("restriction", mode))
+    }
+
+    pub fn update_restricted_addresses(
+        &self,
+        deps: DepsMut,
+        info: MessageInfo,
+        add: Vec<String>,
+        remove: Vec<String>,
+    ) -> Result<Response<C>, ContractError> {
+        self.check_minter(deps.as_ref(), &info)?;
+
+        for addr in add {
+            let addr = deps.api.addr_validate(&addr)?;
+            self.restricted_addresses
+                .save(deps.storage, &addr, &Empty {})?;
+        }
+        for addr in remove {
+            let addr = deps.api.addr_validate(&addr)?;
+            self.restricted_addresses.remove(deps.storage, &addr);
+        }
+
+        Ok(Response::new().add_attribute("action", "update_restricted_addresses"))
+    }
+
+    pub fn update_restricted_code_ids(
+        &self,
+        deps: DepsMut,
+        info: MessageInfo,
+        add: Vec<u64>,
+        remove: Vec<u64>,
+    ) -> Result<Response<C>, ContractError> {
+        self.check_minter(deps.as_ref(), &info)?;
+
+        for code_id in add {
+            self.restricted_code_ids
+                .save(deps.storage, code_id, &Empty {})?;
+        }
+        for code_id in remove {
+            self.restricted_code_ids.remove(deps.storage, code_id);
+        }
+
+        Ok(Response::new().add_attribute("action", "update_restricted_code_ids"))
+    }
+
+    pub fn pause(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+    ) -> Result<Response<C>, ContractError> {
+        self.check_pauser(deps.as_ref(), &info)?;
+        self.check_not_paused(deps.storage)?;
+
+        let pause_info = PauseInfo {
+            paused_by: info.sender.clone(),
+            paused_at: env.block.time,
+        };
+        self.pause_info.save(deps.storage, &pause_info)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "pause")
+            .add_attribute("sender", info.sender))
+    }
+
+    pub fn unpause(&self, deps: DepsMut, info: MessageInfo) -> Result<Response<C>, ContractError> {
+        self.check_pauser(deps.as_ref(), &info)?;
+        if self.pause_info.may_load(deps.storage)?.is_none() {
+            return Err(ContractError::NotPaused {});
+        }
+        self.pause_info.remove(deps.storage);
+
+        Ok(Response::new()
+            .add_attribute("action", "unpause")
+            .add_attribute("sender", info.sender))
+    }
+
+    pub fn set_pauser(
+        &self,
+        deps: DepsMut,
+        info: MessageInfo,
+        pauser: Option<String>,
+    ) -> Result<Response<C>, ContractError> {
+        self.check_minter(deps.as_ref(), &info)?;
+
+        let mut response = Response::new().add_attribute("action", "set_pauser");
+        match pauser {
+            Some(pauser) => {
+                let pauser = deps.api.addr_validate(&pauser)?;
+                self.pauser.save(deps.storage, &pauser)?;
+                response = response.add_attribute("pauser", pauser);
+            }
+            None => self.pauser.remove(deps.storage),
+        }
+        Ok(response)
+    }
+
+    pub fn prune_expired(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        owner: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> Result<Response<C>, ContractError> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let owner_addr = deps.api.addr_validate(&owner)?;
+        let start_addr = maybe_addr(deps.api, start_after)?;
+        let start = start_addr.as_ref().map(Bound::exclusive);
+
+        // the limit bounds the grants scanned, not just the ones pruned
+        let scanned = self
+            .operators
+            .prefix(&owner_addr)
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .collect::<StdResult<Vec<_>>>()?;
+        let mut pruned = 0;
+        for (operator, expires) in &scanned {
+            if expires.is_expired(&env.block) {
+                self.operators.remove(deps.storage, (&owner_addr, operator));
+                self.operator_grants
+                    .remove(deps.storage, (operator, &owner_addr));
+                pruned += 1;
+            }
+        }
+
+        let mut response = Response::new()
+            .add_attribute("action", "prune_expired")
+            .add_attribute("owner", owner)
+            .add_attribute("pruned", pruned.to_string());
+        // where the next call picks up, absent once every grant has been scanned
+        if scanned.len() == limit {
+            if let Some((operator, _)) = scanned.last() {
+                response = response.add_attribute("last_scanned", operator);
+            }
+        }
+        Ok(response)
+    }
 }
 
 impl<'a, T, C, M> Cw721Execute<T, C, M> for Cw721Contract<'a, T, C, M>
@@ -212,6 +528,11 @@ where
         operator: String,
         expires: Option<Expiration>,
     ) -> Result<Response<C>, ContractError> {
+        self.check_not_paused(deps.storage)?;
+        if self.is_soulbound(deps.storage)? {
+            return Err(ContractError::Soulbound {});
+        }
+
         // reject expired data as invalid
         let expires = expires.unwrap_or_default();
         if expires.is_expired(&env.block) {
@@ -222,6 +543,8 @@ where
         let operator_addr = deps.api.addr_validate(&operator)?;
         self.operators
             .save(deps.storage, (&info.sender, &operator_addr), &expires)?;
+        self.operator_grants
+            .save(deps.storage, (&operator_addr, &info.sender), &expires)?;
 
         Ok(Response::new()
             .add_attribute("action", "approve_all")
@@ -239,6 +562,8 @@ where
         let operator_addr = deps.api.addr_validate(&operator)?;
         self.operators
             .remove(deps.storage, (&info.sender, &operator_addr));
+        self.operator_grants
+            .remove(deps.storage, (&operator_addr, &info.sender));
 
         Ok(Response::new()
             .add_attribute("action", "revoke_all")
@@ -253,11 +578,19 @@ where
         info: MessageInfo,
         token_id: String,
     ) -> Result<Response<C>, ContractError> {
-        let token = self.tokens.load(deps.storage, &token_id)?;
-        self.check_can_send(deps.as_ref(), &env, &info, &token)?;
+        self.check_not_paused(deps.storage)?;
+        let mut token = self.tokens.load(deps.storage, &token_id)?;
+        // the minter can revoke soulbound tokens, as nobody else can move them
+        let revoked_by_minter =
+            self.is_soulbound(deps.storage)? && self.minter.load(deps.storage)? == info.sender;
+        if !revoked_by_minter {
+            self.check_can_send(deps.as_ref(), &env, &info, &token)?;
+        }
 
+        self.clear_approvals(deps.storage, &token_id, &mut token);
         self.tokens.remove(deps.storage, &token_id)?;
         self.decrement_tokens(deps.storage)?;
+        self.decrement_balance(deps.storage, &token.owner)?;
 
         Ok(Response::new()
             .add_attribute("action", "burn")
@@ -281,12 +614,18 @@ where
         recipient: &str,
         token_id: &str,
     ) -> Result<TokenInfo<T>, ContractError> {
+        self.check_not_paused(deps.storage)?;
+        if self.is_soulbound(deps.storage)? {
+            return Err(ContractError::Soulbound {});
+        }
         let mut token = self.tokens.load(deps.storage, token_id)?;
         // ensure we have permissions
         self.check_can_send(deps.as_ref(), env, info, &token)?;
+        let recipient = deps.api.addr_validate(recipient)?;
+        self.check_recipient(deps.as_ref(), &recipient)?;
         // set owner and remove existing approvals
-        token.owner = deps.api.addr_validate(recipient)?;
-        token.approvals = vec![];
+        self.set_owner(deps.storage, &mut token, recipient)?;
+        self.clear_approvals(deps.storage, token_id, &mut token);
         self.tokens.save(deps.storage, token_id, &token)?;
         Ok(token)
     }
@@ -306,22 +645,28 @@ where
         let mut token = self.tokens.load(deps.storage, token_id)?;
         // ensure we have permissions
         self.check_can_approve(deps.as_ref(), env, info, &token)?;
+        self.prune_approvals(deps.storage, &env.block, token_id, &mut token);
 
         // update the approval list (remove any for the same spender before adding)
         let spender_addr = deps.api.addr_validate(spender)?;
-        token.approvals = token
-            .approvals
-            .into_iter()
-            .filter(|apr| apr.spender != spender_addr)
-            .collect();
+        token.approvals.retain(|apr| apr.spender != spender_addr);
+        self.spender_approvals
+            .remove(deps.storage, (&spender_addr, token_id));
 
         // only difference between approve and revoke
         if add {
+            self.check_not_paused(deps.storage)?;
+            if self.is_soulbound(deps.storage)? {
+                return Err(ContractError::Soulbound {});
+            }
+
             // reject expired data as invalid
             let expires = expires.unwrap_or_default();
             if expires.is_expired(&env.block) {
                 return Err(ContractError::Expired {});
             }
+            self.spender_approvals
+                .save(deps.storage, (&spender_addr, token_id), &expires)?;
             let approval = Approval {
                 spender: spender_addr,
                 expires,
@@ -334,6 +679,74 @@ where
         Ok(token)
     }
 
+    fn check_minter(&self, deps: Deps, info: &MessageInfo) -> Result<(), ContractError> {
+        let minter = self.minter.load(deps.storage)?;
+        if info.sender != minter {
+            return Err(ContractError::Unauthorized {});
+        }
+        Ok(())
+    }
+
+    /// the minter and the pauser can both pause and unpause
+    fn check_pauser(&self, deps: Deps, info: &MessageInfo) -> Result<(), ContractError> {
+        let minter = self.minter.load(deps.storage)?;
+        let pauser = self.pauser.may_load(deps.storage)?;
+        if info.sender == minter || Some(&info.sender) == pauser.as_ref() {
+            Ok(())
+        } else {
+            Err(ContractError::Unauthorized {})
+        }
+    }
+
+    /// returns an error while the contract is paused
+    pub fn check_not_paused(&self, storage: &dyn Storage) -> Result<(), ContractError> {
+        if self.pause_info.may_load(storage)?.is_some() {
+            return Err(ContractError::Paused {});
+        }
+        Ok(())
+    }
+
+    /// returns an error if the transfer restriction doesn't let the recipient receive tokens
+    pub fn check_recipient(&self, deps: Deps, recipient: &Addr) -> Result<(), ContractError> {
+        let restriction = match self.transfer_restriction.may_load(deps.storage)? {
+            Some(restriction) => restriction,
+            None => return Ok(()),
+        };
+
+        let mut listed = self.restricted_addresses.has(deps.storage, recipient);
+        // only contracts have a code id, the query fails for any other address
+        let has_code_ids = self
+            .restricted_code_ids
+            .keys(deps.storage, None, None, Order::Ascending)
+            .next()
+            .is_some();
+        if !listed && has_code_ids {
+            let contract_info: StdResult<WasmContractInfoResponse> = deps.querier.query(
+                &WasmQuery::ContractInfo {
+                    contract_addr: recipient.to_string(),
+                }
+                .into(),
+            );
+            if let Ok(contract_info) = contract_info {
+                listed = self
+                    .restricted_code_ids
+                    .has(deps.storage, contract_info.code_id);
+            }
+        }
+
+        let allowed = match restriction {
+            TransferRestriction::Allowlist => listed,
+            TransferRestriction::Denylist => !listed,
+        };
+        if allowed {
+            Ok(())
+        } else {
+            Err(ContractError::RecipientNotAllowed {
+                recipient: recipient.to_string(),
+            })
+        }
+    }
+
     /// returns true iff the sender can execute approve or reject on the contract
     pub fn check_can_approve(
         &self,
@@ -1,8 +1,12 @@
-use crate::{ExecuteMsg, QueryMsg};
+use crate::{
+    AllOwnersResponse, ApprovalsBySpenderResponse, BalanceResponse, ExecuteMsg,
+    OwnersByOperatorResponse, QueryMsg, TraitCountsResponse, TraitQueryMsg,
+};
 use cosmwasm_std::{to_binary, Addr, CosmosMsg, QuerierWrapper, StdResult, WasmMsg, WasmQuery};
 use cw721::{
     AllNftInfoResponse, Approval, ApprovalResponse, ApprovalsResponse, ContractInfoResponse,
-    NftInfoResponse, NumTokensResponse, OperatorsResponse, OwnerOfResponse, TokensResponse,
+    ContractMetadataResponse, NftInfoResponse, NumTokensResponse, OperatorsResponse,
+    OwnerOfResponse, TokensResponse, TokensWithInfoResponse,
 };
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
@@ -16,7 +20,7 @@ impl Cw721Contract {
         self.0.clone()
     }
 
-    pub fn call<T: Serialize>(&self, msg: ExecuteMsg<T>) -> StdResult<CosmosMsg> {
+    pub fn call<T: Serialize, M: Serialize>(&self, msg: ExecuteMsg<T, M>) -> StdResult<CosmosMsg> {
         let msg = to_binary(&msg)?;
         Ok(WasmMsg::Execute {
             contract_addr: self.addr().into(),
@@ -39,6 +43,20 @@ impl Cw721Contract {
         querier.query(&query)
     }
 
+    /// Sends `msg` as `QueryMsg::Extension { msg }`
+    pub fn query_extension<T: DeserializeOwned, Q: Serialize>(
+        &self,
+        querier: &QuerierWrapper,
+        msg: Q,
+    ) -> StdResult<T> {
+        let query = WasmQuery::Smart {
+            contract_addr: self.addr().into(),
+            msg: to_binary(&QueryMsg::Extension { msg })?,
+        }
+        .into();
+        querier.query(&query)
+    }
+
     /*** queries ***/
 
     pub fn owner_of<T: Into<String>>(
@@ -102,6 +120,58 @@ impl Cw721Contract {
         Ok(res.operators)
     }
 
+    pub fn balance<T: Into<String>>(&self, querier: &QuerierWrapper, owner: T) -> StdResult<u64> {
+        let req = QueryMsg::Balance {
+            owner: owner.into(),
+        };
+        let res: BalanceResponse = self.query(querier, req)?;
+        Ok(res.balance)
+    }
+
+    pub fn all_owners(
+        &self,
+        querier: &QuerierWrapper,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<AllOwnersResponse> {
+        let req = QueryMsg::AllOwners { start_after, limit };
+        self.query(querier, req)
+    }
+
+    pub fn approvals_by_spender<T: Into<String>>(
+        &self,
+        querier: &QuerierWrapper,
+        spender: T,
+        include_expired: bool,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<ApprovalsBySpenderResponse> {
+        let req = QueryMsg::ApprovalsBySpender {
+            spender: spender.into(),
+            include_expired: Some(include_expired),
+            start_after,
+            limit,
+        };
+        self.query(querier, req)
+    }
+
+    pub fn owners_by_operator<T: Into<String>>(
+        &self,
+        querier: &QuerierWrapper,
+        operator: T,
+        include_expired: bool,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<OwnersByOperatorResponse> {
+        let req = QueryMsg::OwnersByOperator {
+            operator: operator.into(),
+            include_expired: Some(include_expired),
+            start_after,
+            limit,
+        };
+        self.query(querier, req)
+    }
+
     pub fn num_tokens(&self, querier: &QuerierWrapper) -> StdResult<u64> {
         let req = QueryMsg::NumTokens {};
         let res: NumTokensResponse = self.query(querier, req)?;
@@ -114,6 +184,14 @@ impl Cw721Contract {
         self.query(querier, req)
     }
 
+    pub fn contract_metadata<M: DeserializeOwned>(
+        &self,
+        querier: &QuerierWrapper,
+    ) -> StdResult<ContractMetadataResponse<M>> {
+        let req = QueryMsg::ContractMetadata {};
+        self.query(querier, req)
+    }
+
     /// With metadata extension
     pub fn nft_info<T: Into<String>, U: DeserializeOwned>(
         &self,
@@ -167,6 +245,67 @@ impl Cw721Contract {
         self.query(querier, req)
     }
 
+    /// With enumerable and metadata extensions
+    pub fn tokens_with_info<T: Into<String>, U: DeserializeOwned>(
+        &self,
+        querier: &QuerierWrapper,
+        owner: T,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<TokensWithInfoResponse<U>> {
+        let req = QueryMsg::TokensWithInfo {
+            owner: owner.into(),
+            start_after,
+            limit,
+        };
+        self.query(querier, req)
+    }
+
+    /// With enumerable and metadata extensions
+    pub fn all_tokens_with_info<U: DeserializeOwned>(
+        &self,
+        querier: &QuerierWrapper,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<TokensWithInfoResponse<U>> {
+        let req = QueryMsg::AllTokensWithInfo { start_after, limit };
+        self.query(querier, req)
+    }
+
+    /// With attribute index, sent as an extension query
+    pub fn tokens_by_trait<T: Into<String>>(
+        &self,
+        querier: &QuerierWrapper,
+        trait_type: T,
+        value: T,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<TokensResponse> {
+        let msg = TraitQueryMsg::TokensByTrait {
+            trait_type: trait_type.into(),
+            value: value.into(),
+            start_after,
+            limit,
+        };
+        self.query_extension(querier, msg)
+    }
+
+    /// With attribute index, sent as an extension query
+    pub fn trait_counts<T: Into<String>>(
+        &self,
+        querier: &QuerierWrapper,
+        trait_type: T,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<TraitCountsResponse> {
+        let msg = TraitQueryMsg::TraitCounts {
+            trait_type: trait_type.into(),
+            start_after,
+            limit,
+        };
+        self.query_extension(querier, msg)
+    }
+
     /// returns true if the contract supports the metadata extension
     pub fn has_metadata(&self, querier: &QuerierWrapper) -> bool {
         self.contract_info(querier).is_ok()
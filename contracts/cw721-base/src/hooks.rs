@@ -0,0 +1,138 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use cosmwasm_std::{Deps, DepsMut, Env, MessageInfo, Response};
+
+use cw721::CustomMsg;
+
+use crate::error::ContractError;
+use crate::msg::ExecuteMsg;
+use crate::state::{Cw721Contract, TokenInfo};
+
+/// A change to a single existing token that hooks are run for
+#[derive(Clone, Debug, PartialEq)]
+pub enum TokenAction {
+    Transfer { token_id: String, recipient: String },
+    Send { token_id: String, contract: String },
+    Approve { token_id: String, spender: String },
+    Revoke { token_id: String, spender: String },
+    Burn { token_id: String },
+}
+
+impl TokenAction {
+    /// The action an execute message takes on a token, if any.
+    /// Minting and operator approvals don't touch an existing token
+    pub fn from_msg<T, M>(msg: &ExecuteMsg<T, M>) -> Option<Self> {
+        match msg {
+            ExecuteMsg::TransferNft {
+                recipient,
+                token_id,
+            } => Some(TokenAction::Transfer {
+                token_id: token_id.clone(),
+                recipient: recipient.clone(),
+            }),
+            ExecuteMsg::SendNft {
+                contract, token_id, ..
+            }
+            | ExecuteMsg::SafeSendNft {
+                contract, token_id, ..
+            } => Some(TokenAction::Send {
+                token_id: token_id.clone(),
+                contract: contract.clone(),
+            }),
+            ExecuteMsg::Approve {
+                spender, token_id, ..
+            } => Some(TokenAction::Approve {
+                token_id: token_id.clone(),
+                spender: spender.clone(),
+            }),
+            ExecuteMsg::Revoke { spender, token_id } => Some(TokenAction::Revoke {
+                token_id: token_id.clone(),
+                spender: spender.clone(),
+            }),
+            ExecuteMsg::Burn { token_id } => Some(TokenAction::Burn {
+                token_id: token_id.clone(),
+            }),
+            _ => None,
+        }
+    }
+
+    pub fn token_id(&self) -> &str {
+        match self {
+            TokenAction::Transfer { token_id, .. }
+            | TokenAction::Send { token_id, .. }
+            | TokenAction::Approve { token_id, .. }
+            | TokenAction::Revoke { token_id, .. }
+            | TokenAction::Burn { token_id } => token_id,
+        }
+    }
+}
+
+/// Lets contracts extending cw721-base plug their own rules into every transfer,
+/// send, approval and burn. `E` is the error type of the extending contract
+pub trait Cw721Hook<T, C, E = ContractError> {
+    /// Runs before the token is changed, an error aborts the action
+    fn before(
+        &self,
+        _deps: Deps,
+        _env: &Env,
+        _info: &MessageInfo,
+        _action: &TokenAction,
+        _token: &TokenInfo<T>,
+    ) -> Result<(), E> {
+        Ok(())
+    }
+
+    /// Runs after the token was changed and may extend the response
+    fn after(
+        &self,
+        _deps: DepsMut,
+        _env: &Env,
+        _info: &MessageInfo,
+        _action: &TokenAction,
+        response: Response<C>,
+    ) -> Result<Response<C>, E> {
+        Ok(response)
+    }
+}
+
+impl<'a, T, C, M> Cw721Contract<'a, T, C, M>
+where
+    T: Serialize + DeserializeOwned + Clone,
+    M: Serialize + DeserializeOwned + Clone,
+    C: CustomMsg,
+{
+    /// Same as `execute`, running the given hooks in order around any action on an
+    /// existing token
+    pub fn execute_with_hooks<E>(
+        &self,
+        mut deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        msg: ExecuteMsg<T, M>,
+        hooks: &[&dyn Cw721Hook<T, C, E>],
+    ) -> Result<Response<C>, E>
+    where
+        E: From<ContractError>,
+    {
+        let action = TokenAction::from_msg(&msg);
+        if let Some(action) = &action {
+            let token = self
+                .tokens
+                .load(deps.storage, action.token_id())
+                .map_err(ContractError::from)?;
+            for hook in hooks {
+                hook.before(deps.as_ref(), &env, &info, action, &token)?;
+            }
+        }
+
+        let mut response = self.execute(deps.branch(), env.clone(), info.clone(), msg)?;
+
+        if let Some(action) = &action {
+            for hook in hooks {
+                response = hook.after(deps.branch(), &env, &info, action, response)?;
+            }
+        }
+        Ok(response)
+    }
+}
@@ -1,13 +1,24 @@
+pub mod attributes;
 mod contract_tests;
 mod error;
 mod execute;
 pub mod helpers;
+pub mod hooks;
 pub mod msg;
 mod query;
 pub mod state;
 
+pub use crate::attributes::Attributes;
 pub use crate::error::ContractError;
-pub use crate::msg::{ExecuteMsg, InstantiateMsg, MintMsg, MinterResponse, QueryMsg};
+pub use crate::execute::SAFE_SEND_REPLY_ID;
+pub use crate::hooks::{Cw721Hook, TokenAction};
+pub use crate::msg::{
+    AllOwnersResponse, ApprovalsBySpenderResponse, BalanceResponse, ExecuteMsg, InstantiateMsg,
+    MintMsg, MinterResponse, OperatorGrant, OwnerBalance, OwnersByOperatorResponse,
+    PauseInfoResponse, QueryMsg, RestrictedAddressesResponse, RestrictedCodeIdsResponse,
+    SpenderApproval, TraitCount, TraitCountsResponse, TraitQueryMsg, TransferRestrictionResponse,
+    TransferableResponse,
+};
 pub use crate::state::Cw721Contract;
 use cosmwasm_std::Empty;
 
@@ -19,7 +30,7 @@ pub mod entry {
     use super::*;
 
     use cosmwasm_std::entry_point;
-    use cosmwasm_std::{Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
+    use cosmwasm_std::{Binary, Deps, DepsMut, Env, MessageInfo, Reply, Response, StdResult};
 
     // This makes a conscious choice on the various generics used by the contract
     #[entry_point]
@@ -49,4 +60,10 @@ pub mod entry {
         let tract = Cw721Contract::<Extension, Empty, Empty>::default();
         tract.query(deps, env, msg)
     }
+
+    #[entry_point]
+    pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+        let tract = Cw721Contract::<Extension, Empty, Empty>::default();
+        tract.reply(deps, env, msg)
+    }
 }
@@ -1,11 +1,13 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::Binary;
+use cosmwasm_std::{Binary, Empty};
 use cw721::Expiration;
 
+use crate::state::{PauseInfo, TransferRestriction};
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-pub struct InstantiateMsg {
+pub struct InstantiateMsg<M = Empty> {
     /// Name of the NFT contract
     pub name: String,
     /// Symbol of the NFT contract
@@ -15,6 +17,15 @@ pub struct InstantiateMsg {
     /// This is designed for a base NFT that is controlled by an external program
     /// or contract. You will likely replace this with custom logic in custom NFTs
     pub minter: String,
+
+    /// Soulbound tokens can never be transferred, sent or approved once minted.
+    /// They can still be burned by their owner or the minter
+    #[serde(default)]
+    pub soulbound: bool,
+
+    /// Initial contract wide metadata, can be set later with `UpdateContractMetadata`
+    #[serde(default)]
+    pub contract_metadata: Option<M>,
 }
 
 /// This is like Cw721ExecuteMsg but we add a Mint command for an owner
@@ -22,7 +33,7 @@ pub struct InstantiateMsg {
 /// use other control logic in any contract that inherits this.
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 #[serde(rename_all = "snake_case")]
-pub enum ExecuteMsg<T> {
+pub enum ExecuteMsg<T, M = Empty> {
     /// Transfer is a base message to move a token to another account without triggering actions
     TransferNft { recipient: String, token_id: String },
     /// Send is a base message to transfer a token to a contract and trigger an action
@@ -32,6 +43,17 @@ pub enum ExecuteMsg<T> {
         token_id: String,
         msg: Binary,
     },
+    /// Same as `SendNft`, but the whole send is rolled back with a `ReceiverRejected`
+    /// error naming the contract if the receiver fails to handle the token.
+    /// With `check_receiver` set, the receiver must also answer `AcceptsNfts`
+    /// for this contract with `true` before anything is sent
+    SafeSendNft {
+        contract: String,
+        token_id: String,
+        msg: Binary,
+        #[serde(default)]
+        check_receiver: bool,
+    },
     /// Allows operator to transfer / send the token from the owner's account.
     /// If expiration is set, then this allowance has a time/height limit
     Approve {
@@ -55,6 +77,39 @@ pub enum ExecuteMsg<T> {
 
     /// Burn an NFT the sender has access to
     Burn { token_id: String },
+
+    /// Sets whether the restricted addresses and code ids are an allowlist or
+    /// a denylist for recipients, or lifts the restriction if unset.
+    /// Can only be called by the contract minter
+    SetTransferRestriction {
+        restriction: Option<TransferRestriction>,
+    },
+    /// Adds and removes restricted recipient addresses, can only be called by the contract minter
+    UpdateRestrictedAddresses {
+        add: Vec<String>,
+        remove: Vec<String>,
+    },
+    /// Adds and removes restricted recipient contract code ids, can only be called by the contract minter
+    UpdateRestrictedCodeIds { add: Vec<u64>, remove: Vec<u64> },
+
+    /// Stops all mints, transfers, sends, approvals and burns until unpaused.
+    /// Can only be called by the contract minter or pauser
+    Pause {},
+    /// Lifts a pause, can only be called by the contract minter or pauser
+    Unpause {},
+    /// Sets or removes the account that can pause the contract besides the minter.
+    /// Can only be called by the contract minter
+    SetPauser { pauser: Option<String> },
+    /// Scans up to `limit` operator grants made by `owner` after `start_after` and removes
+    /// the expired ones. Anyone can call this, page through with the `last_scanned` attribute
+    /// of the response until it is missing
+    PruneExpired {
+        owner: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Replaces the contract wide metadata, can only be called by the contract minter
+    UpdateContractMetadata(M),
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -73,7 +128,7 @@ pub struct MintMsg<T> {
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
-pub enum QueryMsg {
+pub enum QueryMsg<Q = Empty> {
     /// Return the owner of the given token, error if token does not exist
     /// Return type: OwnerOfResponse
     OwnerOf {
@@ -112,6 +167,8 @@ pub enum QueryMsg {
     /// With MetaData Extension.
     /// Returns top-level metadata about the contract: `ContractInfoResponse`
     ContractInfo {},
+    /// Returns the contract wide metadata, if any: `ContractMetadataResponse`
+    ContractMetadata {},
     /// With MetaData Extension.
     /// Returns metadata about one particular token, based on *ERC721 Metadata JSON Schema*
     /// but directly from the contract: `NftInfoResponse`
@@ -142,9 +199,109 @@ pub enum QueryMsg {
         start_after: Option<String>,
         limit: Option<u32>,
     },
+    /// With Enumerable extension.
+    /// Same as `Tokens`, but returns the `AllNftInfo` of every token along with its id.
+    /// Return type: TokensWithInfoResponse.
+    TokensWithInfo {
+        owner: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// With Enumerable extension.
+    /// Same as `AllTokens`, but returns the `AllNftInfo` of every token along with its id.
+    /// Return type: TokensWithInfoResponse.
+    AllTokensWithInfo {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
 
     // Return the minter
     Minter {},
+
+    /// Returns whether the given token can be transferred
+    /// Return type: `TransferableResponse`
+    Transferable {
+        token_id: String,
+    },
+
+    /// Returns how recipients are restricted
+    /// Return type: `TransferRestrictionResponse`
+    TransferRestriction {},
+    /// Lists the restricted recipient addresses
+    /// Return type: `RestrictedAddressesResponse`
+    RestrictedAddresses {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Lists the restricted recipient contract code ids
+    /// Return type: `RestrictedCodeIdsResponse`
+    RestrictedCodeIds {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+
+    /// Returns the pauser and, if the contract is paused, who paused it and when
+    /// Return type: `PauseInfoResponse`
+    PauseInfo {},
+
+    /// Returns the number of tokens held by the given owner
+    /// Return type: `BalanceResponse`
+    Balance {
+        owner: String,
+    },
+    /// Lists every address holding at least one token along with its balance
+    /// Return type: `AllOwnersResponse`
+    AllOwners {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    /// Lists the tokens a spender has been approved for, by token id
+    /// Return type: `ApprovalsBySpenderResponse`
+    ApprovalsBySpender {
+        spender: String,
+        /// unset or false will filter out expired approvals, you must set to true to see them
+        include_expired: Option<bool>,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Lists the owners that have made the given address their operator
+    /// Return type: `OwnersByOperatorResponse`
+    OwnersByOperator {
+        operator: String,
+        /// unset or false will filter out expired items, you must set to true to see them
+        include_expired: Option<bool>,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    /// Queries added by a contract extending this one, answered by the handler
+    /// it passes to `query_with_extension`
+    Extension {
+        msg: Q,
+    },
+}
+
+/// Queries of the trait index, for contracts that keep it with `execute_indexed`
+/// to serve as `QueryMsg::Extension { msg }`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TraitQueryMsg {
+    /// Lists the tokens that have the given trait
+    /// Return type: TokensResponse.
+    TokensByTrait {
+        trait_type: String,
+        value: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Returns how many tokens have each value of the given trait type, by value.
+    /// Return type: `TraitCountsResponse`
+    TraitCounts {
+        trait_type: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
 }
 
 /// Shows who can mint these tokens
@@ -152,3 +309,80 @@ pub enum QueryMsg {
 pub struct MinterResponse {
     pub minter: String,
 }
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct TransferableResponse {
+    pub transferable: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct TransferRestrictionResponse {
+    pub restriction: Option<TransferRestriction>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct RestrictedAddressesResponse {
+    pub addresses: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct RestrictedCodeIdsResponse {
+    pub code_ids: Vec<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct PauseInfoResponse {
+    pub pauser: Option<String>,
+    /// Set while the contract is paused
+    pub paused: Option<PauseInfo>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct SpenderApproval {
+    pub token_id: String,
+    pub expires: Expiration,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ApprovalsBySpenderResponse {
+    pub approvals: Vec<SpenderApproval>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct OperatorGrant {
+    pub owner: String,
+    pub expires: Expiration,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct OwnersByOperatorResponse {
+    pub owners: Vec<OperatorGrant>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct BalanceResponse {
+    pub balance: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct OwnerBalance {
+    pub owner: String,
+    pub balance: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct AllOwnersResponse {
+    pub owners: Vec<OwnerBalance>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct TraitCount {
+    pub value: String,
+    pub count: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct TraitCountsResponse {
+    pub trait_type: String,
+    pub counts: Vec<TraitCount>,
+}
@@ -4,18 +4,23 @@ use serde::Serialize;
 use cosmwasm_std::{to_binary, Addr, Binary, BlockInfo, Deps, Env, Order, StdError, StdResult};
 
 use cw721::{
-    AllNftInfoResponse, ApprovalResponse, ApprovalsResponse, ContractInfoResponse, CustomMsg,
-    Cw721Query, Expiration, NftInfoResponse, NumTokensResponse, OperatorsResponse, OwnerOfResponse,
-    TokensResponse,
+    AllNftInfoResponse, ApprovalResponse, ApprovalsResponse, ContractInfoResponse,
+    ContractMetadataResponse, CustomMsg, Cw721Query, Expiration, NftInfoResponse,
+    NumTokensResponse, OperatorsResponse, OwnerOfResponse, TokensResponse, TokensWithInfoResponse,
 };
 use cw_storage_plus::Bound;
 use cw_utils::maybe_addr;
 
-use crate::msg::{MinterResponse, QueryMsg};
+use crate::msg::{
+    AllOwnersResponse, ApprovalsBySpenderResponse, BalanceResponse, MinterResponse, OperatorGrant,
+    OwnerBalance, OwnersByOperatorResponse, PauseInfoResponse, QueryMsg,
+    RestrictedAddressesResponse, RestrictedCodeIdsResponse, SpenderApproval, TraitCount,
+    TraitCountsResponse, TraitQueryMsg, TransferRestrictionResponse, TransferableResponse,
+};
 use crate::state::{Approval, Cw721Contract, TokenInfo};
 
-const DEFAULT_LIMIT: u32 = 10;
-const MAX_LIMIT: u32 = 30;
+pub(crate) const DEFAULT_LIMIT: u32 = 10;
+pub(crate) const MAX_LIMIT: u32 = 30;
 
 impl<'a, T, C, M> Cw721Query<T, M> for Cw721Contract<'a, T, C, M>
 where
@@ -27,6 +32,11 @@ where
         self.contract_info.load(deps.storage)
     }
 
+    fn contract_metadata(&self, deps: Deps) -> StdResult<ContractMetadataResponse<M>> {
+        let metadata = self.contract_metadata.may_load(deps.storage)?;
+        Ok(ContractMetadataResponse { metadata })
+    }
+
     fn num_tokens(&self, deps: Deps) -> StdResult<NumTokensResponse> {
         let count = self.token_count(deps.storage)?;
         Ok(NumTokensResponse { count })
@@ -194,16 +204,54 @@ where
         include_expired: bool,
     ) -> StdResult<AllNftInfoResponse<T>> {
         let info = self.tokens.load(deps.storage, &token_id)?;
-        Ok(AllNftInfoResponse {
-            access: OwnerOfResponse {
-                owner: info.owner.to_string(),
-                approvals: humanize_approvals(&env.block, &info, include_expired),
-            },
-            info: NftInfoResponse {
-                token_uri: info.token_uri,
-                extension: info.extension,
-            },
-        })
+        Ok(humanize_nft_info(&env.block, info, include_expired))
+    }
+
+    fn tokens_with_info(
+        &self,
+        deps: Deps,
+        env: Env,
+        owner: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<TokensWithInfoResponse<T>> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start = start_after.map(|s| Bound::ExclusiveRaw(s.into()));
+
+        let owner_addr = deps.api.addr_validate(&owner)?;
+        let tokens = self
+            .tokens
+            .idx
+            .owner
+            .prefix(owner_addr)
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|item| {
+                item.map(|(k, info)| (k.to_string(), humanize_nft_info(&env.block, info, false)))
+            })
+            .collect::<StdResult<Vec<_>>>()?;
+
+        Ok(TokensWithInfoResponse { tokens })
+    }
+
+    fn all_tokens_with_info(
+        &self,
+        deps: Deps,
+        env: Env,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<TokensWithInfoResponse<T>> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start = start_after.map(|s| Bound::ExclusiveRaw(s.into()));
+
+        let tokens = self
+            .tokens
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|item| item.map(|(k, info)| (k, humanize_nft_info(&env.block, info, false))))
+            .collect::<StdResult<Vec<_>>>()?;
+
+        Ok(TokensWithInfoResponse { tokens })
     }
 }
 
@@ -220,10 +268,234 @@ where
         })
     }
 
+    pub fn transferable(&self, deps: Deps, token_id: String) -> StdResult<TransferableResponse> {
+        // error if the token does not exist
+        self.tokens.load(deps.storage, &token_id)?;
+        Ok(TransferableResponse {
+            transferable: !self.is_soulbound(deps.storage)?,
+        })
+    }
+
+    pub fn transfer_restriction(&self, deps: Deps) -> StdResult<TransferRestrictionResponse> {
+        Ok(TransferRestrictionResponse {
+            restriction: self.transfer_restriction.may_load(deps.storage)?,
+        })
+    }
+
+    pub fn restricted_addresses(
+        &self,
+        deps: Deps,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<RestrictedAddressesResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start_addr = maybe_addr(deps.api, start_after)?;
+        let start = start_addr.as_ref().map(Bound::exclusive);
+
+        let addresses = self
+            .restricted_addresses
+            .keys(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|addr| addr.map(String::from))
+            .collect::<StdResult<Vec<_>>>()?;
+
+        Ok(RestrictedAddressesResponse { addresses })
+    }
+
+    pub fn restricted_code_ids(
+        &self,
+        deps: Deps,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<RestrictedCodeIdsResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start = start_after.map(Bound::exclusive);
+
+        let code_ids = self
+            .restricted_code_ids
+            .keys(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .collect::<StdResult<Vec<_>>>()?;
+
+        Ok(RestrictedCodeIdsResponse { code_ids })
+    }
+
+    pub fn pause_info(&self, deps: Deps) -> StdResult<PauseInfoResponse> {
+        Ok(PauseInfoResponse {
+            pauser: self.pauser.may_load(deps.storage)?.map(String::from),
+            paused: self.pause_info.may_load(deps.storage)?,
+        })
+    }
+
+    pub fn balance(&self, deps: Deps, owner: String) -> StdResult<BalanceResponse> {
+        let owner_addr = deps.api.addr_validate(&owner)?;
+        let balance = self.balance_of(deps.storage, &owner_addr)?;
+        Ok(BalanceResponse { balance })
+    }
+
+    pub fn all_owners(
+        &self,
+        deps: Deps,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<AllOwnersResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start_addr = maybe_addr(deps.api, start_after)?;
+        let start = start_addr.as_ref().map(Bound::exclusive);
+
+        let owners = self
+            .balances
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|item| {
+                item.map(|(owner, balance)| OwnerBalance {
+                    owner: owner.into_string(),
+                    balance,
+                })
+            })
+            .collect::<StdResult<Vec<_>>>()?;
+
+        Ok(AllOwnersResponse { owners })
+    }
+
+    pub fn tokens_by_trait(
+        &self,
+        deps: Deps,
+        trait_type: String,
+        value: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<TokensResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start = start_after.as_deref().map(Bound::exclusive);
+
+        let tokens = self
+            .trait_tokens
+            .prefix((&trait_type, &value))
+            .keys(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .collect::<StdResult<Vec<_>>>()?;
+
+        Ok(TokensResponse { tokens })
+    }
+
+    pub fn trait_counts(
+        &self,
+        deps: Deps,
+        trait_type: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<TraitCountsResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start = start_after.as_deref().map(Bound::exclusive);
+
+        let counts = self
+            .trait_counts
+            .prefix(&trait_type)
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|item| item.map(|(value, count)| TraitCount { value, count }))
+            .collect::<StdResult<Vec<_>>>()?;
+
+        Ok(TraitCountsResponse { trait_type, counts })
+    }
+
+    /// Answers a `TraitQueryMsg`, for contracts that keep the trait index to call
+    /// from their `QueryMsg::Extension` handler
+    pub fn query_traits(&self, deps: Deps, msg: TraitQueryMsg) -> StdResult<Binary> {
+        match msg {
+            TraitQueryMsg::TokensByTrait {
+                trait_type,
+                value,
+                start_after,
+                limit,
+            } => to_binary(&self.tokens_by_trait(deps, trait_type, value, start_after, limit)?),
+            TraitQueryMsg::TraitCounts {
+                trait_type,
+                start_after,
+                limit,
+            } => to_binary(&self.trait_counts(deps, trait_type, start_after, limit)?),
+        }
+    }
+
+    pub fn approvals_by_spender(
+        &self,
+        deps: Deps,
+        env: Env,
+        spender: String,
+        include_expired: bool,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<ApprovalsBySpenderResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start = start_after.as_deref().map(Bound::exclusive);
+
+        let spender_addr = deps.api.addr_validate(&spender)?;
+        let approvals = self
+            .spender_approvals
+            .prefix(&spender_addr)
+            .range(deps.storage, start, None, Order::Ascending)
+            .filter(|r| {
+                include_expired || r.is_err() || !r.as_ref().unwrap().1.is_expired(&env.block)
+            })
+            .take(limit)
+            .map(|item| item.map(|(token_id, expires)| SpenderApproval { token_id, expires }))
+            .collect::<StdResult<Vec<_>>>()?;
+
+        Ok(ApprovalsBySpenderResponse { approvals })
+    }
+
+    pub fn owners_by_operator(
+        &self,
+        deps: Deps,
+        env: Env,
+        operator: String,
+        include_expired: bool,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<OwnersByOperatorResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start_addr = maybe_addr(deps.api, start_after)?;
+        let start = start_addr.as_ref().map(Bound::exclusive);
+
+        let operator_addr = deps.api.addr_validate(&operator)?;
+        let owners = self
+            .operator_grants
+            .prefix(&operator_addr)
+            .range(deps.storage, start, None, Order::Ascending)
+            .filter(|r| {
+                include_expired || r.is_err() || !r.as_ref().unwrap().1.is_expired(&env.block)
+            })
+            .take(limit)
+            .map(|item| {
+                item.map(|(owner, expires)| OperatorGrant {
+                    owner: owner.into_string(),
+                    expires,
+                })
+            })
+            .collect::<StdResult<Vec<_>>>()?;
+
+        Ok(OwnersByOperatorResponse { owners })
+    }
+
     pub fn query(&self, deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+        self.query_with_extension(deps, env, msg, |_, _, _| {
+            Err(StdError::generic_err("Extension queries are not supported"))
+        })
+    }
+
+    /// Same as `query`, passing `QueryMsg::Extension` on to the `extension` handler
+    pub fn query_with_extension<Q>(
+        &self,
+        deps: Deps,
+        env: Env,
+        msg: QueryMsg<Q>,
+        extension: impl FnOnce(Deps, Env, Q) -> StdResult<Binary>,
+    ) -> StdResult<Binary> {
         match msg {
             QueryMsg::Minter {} => to_binary(&self.minter(deps)?),
             QueryMsg::ContractInfo {} => to_binary(&self.contract_info(deps)?),
+            QueryMsg::ContractMetadata {} => to_binary(&self.contract_metadata(deps)?),
             QueryMsg::NftInfo { token_id } => to_binary(&self.nft_info(deps, token_id)?),
             QueryMsg::OwnerOf {
                 token_id,
@@ -262,6 +534,14 @@ where
             QueryMsg::AllTokens { start_after, limit } => {
                 to_binary(&self.all_tokens(deps, start_after, limit)?)
             }
+            QueryMsg::TokensWithInfo {
+                owner,
+                start_after,
+                limit,
+            } => to_binary(&self.tokens_with_info(deps, env, owner, start_after, limit)?),
+            QueryMsg::AllTokensWithInfo { start_after, limit } => {
+                to_binary(&self.all_tokens_with_info(deps, env, start_after, limit)?)
+            }
             QueryMsg::Approval {
                 token_id,
                 spender,
@@ -279,6 +559,46 @@ where
             } => {
                 to_binary(&self.approvals(deps, env, token_id, include_expired.unwrap_or(false))?)
             }
+            QueryMsg::Transferable { token_id } => to_binary(&self.transferable(deps, token_id)?),
+            QueryMsg::TransferRestriction {} => to_binary(&self.transfer_restriction(deps)?),
+            QueryMsg::RestrictedAddresses { start_after, limit } => {
+                to_binary(&self.restricted_addresses(deps, start_after, limit)?)
+            }
+            QueryMsg::RestrictedCodeIds { start_after, limit } => {
+                to_binary(&self.restricted_code_ids(deps, start_after, limit)?)
+            }
+            QueryMsg::PauseInfo {} => to_binary(&self.pause_info(deps)?),
+            QueryMsg::Balance { owner } => to_binary(&self.balance(deps, owner)?),
+            QueryMsg::AllOwners { start_after, limit } => {
+                to_binary(&self.all_owners(deps, start_after, limit)?)
+            }
+            QueryMsg::ApprovalsBySpender {
+                spender,
+                include_expired,
+                start_after,
+                limit,
+            } => to_binary(&self.approvals_by_spender(
+                deps,
+                env,
+                spender,
+                include_expired.unwrap_or(false),
+                start_after,
+                limit,
+            )?),
+            QueryMsg::OwnersByOperator {
+                operator,
+                include_expired,
+                start_after,
+                limit,
+            } => to_binary(&self.owners_by_operator(
+                deps,
+                env,
+                operator,
+                include_expired.unwrap_or(false),
+                start_after,
+                limit,
+            )?),
+            QueryMsg::Extension { msg } => extension(deps, env, msg),
         }
     }
 }
@@ -290,6 +610,23 @@ fn parse_approval(item: StdResult<(Addr, Expiration)>) -> StdResult<cw721::Appro
     })
 }
 
+fn humanize_nft_info<T>(
+    block: &BlockInfo,
+    info: TokenInfo<T>,
+    include_expired: bool,
+) -> AllNftInfoResponse<T> {
+    AllNftInfoResponse {
+        access: OwnerOfResponse {
+            owner: info.owner.to_string(),
+            approvals: humanize_approvals(block, &info, include_expired),
+        },
+        info: NftInfoResponse {
+            token_uri: info.token_uri,
+            extension: info.extension,
+        },
+    }
+}
+
 fn humanize_approvals<T>(
     block: &BlockInfo,
     info: &TokenInfo<T>,
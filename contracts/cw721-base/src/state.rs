@@ -3,7 +3,7 @@ use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use std::marker::PhantomData;
 
-use cosmwasm_std::{Addr, BlockInfo, StdResult, Storage};
+use cosmwasm_std::{Addr, BlockInfo, Empty, StdResult, Storage, Timestamp};
 
 use cw721::{ContractInfoResponse, CustomMsg, Cw721, Expiration};
 use cw_storage_plus::{Index, IndexList, IndexedMap, Item, Map, MultiIndex};
@@ -17,8 +17,32 @@ where
     pub contract_metadata: Item<'a, M>,
     pub minter: Item<'a, Addr>,
     pub token_count: Item<'a, u64>,
+    /// Number of tokens held by each owner, owners without tokens are removed
+    pub balances: Map<'a, &'a Addr, u64>,
+    /// When set, tokens stay with the account they were minted to
+    pub soulbound: Item<'a, bool>,
+    /// Limits who tokens can be transferred or sent to, unrestricted if unset
+    pub transfer_restriction: Item<'a, TransferRestriction>,
+    /// Recipients the transfer restriction applies to
+    pub restricted_addresses: Map<'a, &'a Addr, Empty>,
+    /// Code ids of recipient contracts the transfer restriction applies to
+    pub restricted_code_ids: Map<'a, u64, Empty>,
+    /// Account that can pause the contract besides the minter
+    pub pauser: Item<'a, Addr>,
+    /// Set while the contract is paused
+    pub pause_info: Item<'a, PauseInfo>,
+    /// `SafeSendNft`s waiting for the receiver's reply, innermost last
+    pub pending_sends: Item<'a, Vec<PendingSend>>,
     /// Stored as (granter, operator) giving operator full control over granter's account
     pub operators: Map<'a, (&'a Addr, &'a Addr), Expiration>,
+    /// Stored as (operator, granter), the reverse index of `operators`
+    pub operator_grants: Map<'a, (&'a Addr, &'a Addr), Expiration>,
+    /// Stored as (spender, token_id), an index of the approvals held in `TokenInfo`
+    pub spender_approvals: Map<'a, (&'a Addr, &'a str), Expiration>,
+    /// Stored as (trait_type, value, token_id), only filled by `execute_indexed`
+    pub trait_tokens: Map<'a, (&'a str, &'a str, &'a str), Empty>,
+    /// Number of tokens per (trait_type, value), only filled by `execute_indexed`
+    pub trait_counts: Map<'a, (&'a str, &'a str), u64>,
     pub tokens: IndexedMap<'a, &'a str, TokenInfo<T>, TokenIndexes<'a, T>>,
 
     pub(crate) _custom_response: PhantomData<C>,
@@ -44,7 +68,19 @@ where
             "nft_metadata",
             "minter",
             "num_tokens",
+            "balances",
+            "soulbound",
+            "transfer_restriction",
+            "restricted_addresses",
+            "restricted_code_ids",
+            "pauser",
+            "pause_info",
+            "pending_sends",
             "operators",
+            "operator_grants",
+            "spender_approvals",
+            "trait_tokens",
+            "trait_counts",
             "tokens",
             "tokens__owner",
         )
@@ -56,12 +92,25 @@ where
     T: Serialize + DeserializeOwned + Clone,
     M: Serialize + DeserializeOwned + Clone,
 {
+    #[allow(clippy::too_many_arguments)]
     fn new(
         contract_key: &'a str,
         contract_metadata_key: &'a str,
         minter_key: &'a str,
         token_count_key: &'a str,
+        balances_key: &'a str,
+        soulbound_key: &'a str,
+        transfer_restriction_key: &'a str,
+        restricted_addresses_key: &'a str,
+        restricted_code_ids_key: &'a str,
+        pauser_key: &'a str,
+        pause_info_key: &'a str,
+        pending_sends_key: &'a str,
         operator_key: &'a str,
+        operator_grants_key: &'a str,
+        spender_approvals_key: &'a str,
+        trait_tokens_key: &'a str,
+        trait_counts_key: &'a str,
         tokens_key: &'a str,
         tokens_owner_key: &'a str,
     ) -> Self {
@@ -73,7 +122,19 @@ where
             contract_metadata: Item::new(contract_metadata_key),
             minter: Item::new(minter_key),
             token_count: Item::new(token_count_key),
+            balances: Map::new(balances_key),
+            soulbound: Item::new(soulbound_key),
+            transfer_restriction: Item::new(transfer_restriction_key),
+            restricted_addresses: Map::new(restricted_addresses_key),
+            restricted_code_ids: Map::new(restricted_code_ids_key),
+            pauser: Item::new(pauser_key),
+            pause_info: Item::new(pause_info_key),
+            pending_sends: Item::new(pending_sends_key),
             operators: Map::new(operator_key),
+            operator_grants: Map::new(operator_grants_key),
+            spender_approvals: Map::new(spender_approvals_key),
+            trait_tokens: Map::new(trait_tokens_key),
+            trait_counts: Map::new(trait_counts_key),
             tokens: IndexedMap::new(tokens_key, indexes),
             _custom_response: PhantomData,
         }
@@ -83,6 +144,44 @@ where
         Ok(self.token_count.may_load(storage)?.unwrap_or_default())
     }
 
+    pub fn is_soulbound(&self, storage: &dyn Storage) -> StdResult<bool> {
+        Ok(self.soulbound.may_load(storage)?.unwrap_or_default())
+    }
+
+    /// Drops all approvals of a token along with their index entries.
+    /// The token itself still needs to be saved
+    pub fn clear_approvals(
+        &self,
+        storage: &mut dyn Storage,
+        token_id: &str,
+        token: &mut TokenInfo<T>,
+    ) {
+        for approval in token.approvals.drain(..) {
+            self.spender_approvals
+                .remove(storage, (&approval.spender, token_id));
+        }
+    }
+
+    /// Drops the expired approvals of a token along with their index entries.
+    /// The token itself still needs to be saved
+    pub fn prune_approvals(
+        &self,
+        storage: &mut dyn Storage,
+        block: &BlockInfo,
+        token_id: &str,
+        token: &mut TokenInfo<T>,
+    ) {
+        let (expired, live) = token
+            .approvals
+            .drain(..)
+            .partition(|approval| approval.is_expired(block));
+        token.approvals = live;
+        for approval in expired {
+            self.spender_approvals
+                .remove(storage, (&approval.spender, token_id));
+        }
+    }
+
     pub fn increment_tokens(&self, storage: &mut dyn Storage) -> StdResult<u64> {
         let val = self.token_count(storage)? + 1;
         self.token_count.save(storage, &val)?;
@@ -94,6 +193,40 @@ where
         self.token_count.save(storage, &val)?;
         Ok(val)
     }
+
+    pub fn balance_of(&self, storage: &dyn Storage, owner: &Addr) -> StdResult<u64> {
+        Ok(self.balances.may_load(storage, owner)?.unwrap_or_default())
+    }
+
+    pub fn increment_balance(&self, storage: &mut dyn Storage, owner: &Addr) -> StdResult<u64> {
+        let val = self.balance_of(storage, owner)? + 1;
+        self.balances.save(storage, owner, &val)?;
+        Ok(val)
+    }
+
+    pub fn decrement_balance(&self, storage: &mut dyn Storage, owner: &Addr) -> StdResult<u64> {
+        let val = self.balance_of(storage, owner)?.saturating_sub(1);
+        if val == 0 {
+            self.balances.remove(storage, owner);
+        } else {
+            self.balances.save(storage, owner, &val)?;
+        }
+        Ok(val)
+    }
+
+    /// Hands a token to a new owner, keeping the balances in sync.
+    /// The token itself still needs to be saved
+    pub fn set_owner(
+        &self,
+        storage: &mut dyn Storage,
+        token: &mut TokenInfo<T>,
+        owner: Addr,
+    ) -> StdResult<()> {
+        self.decrement_balance(storage, &token.owner)?;
+        self.increment_balance(storage, &owner)?;
+        token.owner = owner;
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -112,6 +245,28 @@ pub struct TokenInfo<T> {
     pub extension: T,
 }
 
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct PauseInfo {
+    pub paused_by: Addr,
+    pub paused_at: Timestamp,
+}
+
+/// A token sent with `SafeSendNft` whose receiver hasn't replied yet
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct PendingSend {
+    pub contract: Addr,
+    pub token_id: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum TransferRestriction {
+    /// Only the listed addresses and code ids can receive tokens
+    Allowlist,
+    /// The listed addresses and code ids can't receive tokens
+    Denylist,
+}
+
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 pub struct Approval {
     /// Account that can transfer/send the token
@@ -1,13 +1,14 @@
 use crate::error::ContractError;
-use crate::msg::{ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg};
-use crate::state::{Config, CONFIG};
+use crate::msg::{ConfigResponse, CurrentPriceResponse, ExecuteMsg, InstantiateMsg, QueryMsg};
+use crate::state::{Config, Purchase, CLEARING_PRICE, CONFIG, PURCHASES};
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    to_binary, Addr, Binary, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Reply, ReplyOn, Response,
-    StdResult, SubMsg, Uint128, WasmMsg,
+    to_binary, Addr, Binary, CosmosMsg, Deps, DepsMut, Empty, Env, MessageInfo, Reply, ReplyOn,
+    Response, StdResult, SubMsg, Timestamp, Uint128, WasmMsg,
 };
 use cw2::set_contract_version;
+use cw20::Cw20ExecuteMsg;
 use cw721_base::{
     msg::ExecuteMsg as Cw721ExecuteMsg, msg::InstantiateMsg as Cw721InstantiateMsg, Extension,
     MintMsg,
@@ -37,6 +38,12 @@ pub fn instantiate(
         return Err(ContractError::InvalidMaxTokens {});
     }
 
+    if let Some(auction) = &msg.dutch_auction {
+        if auction.start_price <= msg.unit_price || auction.end_time <= auction.start_time {
+            return Err(ContractError::InvalidAuctionSchedule {});
+        }
+    }
+
     let config = Config {
         cw721_address: None,
         cw20_address: msg.cw20_address,
@@ -48,6 +55,7 @@ pub fn instantiate(
         token_uri: msg.token_uri.clone(),
         extension: msg.extension.clone(),
         unused_token_id: 0,
+        dutch_auction: msg.dutch_auction,
     };
 
     CONFIG.save(deps.storage, &config)?;
@@ -55,10 +63,12 @@ pub fn instantiate(
     let sub_msg: Vec<SubMsg> = vec![SubMsg {
         msg: WasmMsg::Instantiate {
             code_id: msg.token_code_id,
-            msg: to_binary(&Cw721InstantiateMsg {
+            msg: to_binary(&Cw721InstantiateMsg::<Empty> {
                 name: msg.name.clone(),
                 symbol: msg.symbol,
                 minter: env.contract.address.to_string(),
+                soulbound: false,
+                contract_metadata: None,
             })?,
             funds: vec![],
             admin: None,
@@ -78,7 +88,7 @@ pub fn instantiate(
 pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
     let mut config: Config = CONFIG.load(deps.storage)?;
 
-    if config.cw721_address != None {
+    if config.cw721_address.is_some() {
         return Err(ContractError::Cw721AlreadyLinked {});
     }
 
@@ -94,9 +104,10 @@ pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractE
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::GetConfig {} => to_binary(&query_config(deps)?),
+        QueryMsg::CurrentPrice {} => to_binary(&query_current_price(deps, env)?),
     }
 }
 
@@ -113,25 +124,43 @@ fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
         token_uri: config.token_uri,
         extension: config.extension,
         unused_token_id: config.unused_token_id,
+        dutch_auction: config.dutch_auction,
+    })
+}
+
+fn query_current_price(deps: Deps, env: Env) -> StdResult<CurrentPriceResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    Ok(CurrentPriceResponse {
+        price: current_price(&config, env.block.time),
     })
 }
 
+/// returns the unit price, or the Dutch auction price at the given time
+fn current_price(config: &Config, time: Timestamp) -> Uint128 {
+    match &config.dutch_auction {
+        Some(auction) => auction.price_at(config.unit_price, time),
+        None => config.unit_price,
+    }
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
     match msg {
         ExecuteMsg::Cw20ReceiveMsg { sender, amount } => {
-            execute_receive(deps, info, sender, amount)
+            execute_receive(deps, env, info, sender, amount)
         }
+        ExecuteMsg::ClaimRebate {} => execute_claim_rebate(deps, env, info),
     }
 }
 
 pub fn execute_receive(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     sender: String,
     amount: Uint128,
@@ -141,7 +170,7 @@ pub fn execute_receive(
         return Err(ContractError::UnauthorizedTokenContract {});
     }
 
-    if config.cw721_address == None {
+    if config.cw721_address.is_none() {
         return Err(ContractError::Uninitialized {});
     }
 
@@ -149,11 +178,52 @@ pub fn execute_receive(
         return Err(ContractError::SoldOut {});
     }
 
-    if amount != config.unit_price {
-        return Err(ContractError::WrongPaymentAmount {});
+    let mut messages = vec![];
+    match &config.dutch_auction {
+        None => {
+            if amount != config.unit_price {
+                return Err(ContractError::WrongPaymentAmount {});
+            }
+        }
+        Some(auction) => {
+            if env.block.time < auction.start_time {
+                return Err(ContractError::SaleNotStarted {});
+            }
+
+            // the price keeps moving, so accept overpayment and refund the rest
+            let price = current_price(&config, env.block.time);
+            if amount < price {
+                return Err(ContractError::WrongPaymentAmount {});
+            }
+            if amount > price {
+                messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: config.cw20_address.to_string(),
+                    msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                        recipient: sender.clone(),
+                        amount: amount - price,
+                    })?,
+                    funds: vec![],
+                }));
+            }
+
+            if auction.rebate {
+                let buyer = deps.api.addr_validate(&sender)?;
+                PURCHASES.update(deps.storage, &buyer, |old| -> StdResult<_> {
+                    let mut purchase = old.unwrap_or_default();
+                    purchase.count += 1;
+                    purchase.total_paid += price;
+                    Ok(purchase)
+                })?;
+            }
+
+            // the last token sold sets the clearing price
+            if config.unused_token_id + 1 == config.max_tokens {
+                CLEARING_PRICE.save(deps.storage, &price)?;
+            }
+        }
     }
 
-    let mint_msg = Cw721ExecuteMsg::Mint(MintMsg::<Extension> {
+    let mint_msg: Cw721ExecuteMsg<Extension> = Cw721ExecuteMsg::Mint(MintMsg {
         token_id: config.unused_token_id.to_string(),
         owner: sender,
         token_uri: config.token_uri.clone().into(),
@@ -169,12 +239,56 @@ pub fn execute_receive(
     config.unused_token_id += 1;
     CONFIG.save(deps.storage, &config)?;
 
-    Ok(Response::new().add_message(callback))
+    Ok(Response::new().add_message(callback).add_messages(messages))
+}
+
+pub fn execute_claim_rebate(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let auction = match config.dutch_auction {
+        Some(auction) if auction.rebate => auction,
+        _ => return Err(ContractError::RebateNotEnabled {}),
+    };
+
+    // settled either by selling out or by reaching the floor price
+    let clearing_price = match CLEARING_PRICE.may_load(deps.storage)? {
+        Some(price) => price,
+        None if env.block.time >= auction.end_time => config.unit_price,
+        None => return Err(ContractError::AuctionNotSettled {}),
+    };
+
+    let Purchase { count, total_paid } = PURCHASES
+        .may_load(deps.storage, &info.sender)?
+        .ok_or(ContractError::NoRebate {})?;
+    let rebate = total_paid - clearing_price * Uint128::from(count);
+    if rebate.is_zero() {
+        return Err(ContractError::NoRebate {});
+    }
+    PURCHASES.remove(deps.storage, &info.sender);
+
+    let transfer = CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: config.cw20_address.to_string(),
+        msg: to_binary(&Cw20ExecuteMsg::Transfer {
+            recipient: info.sender.to_string(),
+            amount: rebate,
+        })?,
+        funds: vec![],
+    });
+
+    Ok(Response::new()
+        .add_message(transfer)
+        .add_attribute("action", "claim_rebate")
+        .add_attribute("sender", info.sender)
+        .add_attribute("amount", rebate))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::state::DutchAuction;
     use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info, MOCK_CONTRACT_ADDR};
     use cosmwasm_std::{from_binary, to_binary, SubMsgExecutionResponse, SubMsgResult};
     use prost::Message;
@@ -201,6 +315,7 @@ mod tests {
             cw20_address: Addr::unchecked(MOCK_CONTRACT_ADDR),
             token_uri: String::from("https://ipfs.io/ipfs/Q"),
             extension: None,
+            dutch_auction: None,
         };
 
         let info = mock_info("owner", &[]);
@@ -213,10 +328,12 @@ mod tests {
             vec![SubMsg {
                 msg: WasmMsg::Instantiate {
                     code_id: msg.token_code_id,
-                    msg: to_binary(&Cw721InstantiateMsg {
+                    msg: to_binary(&Cw721InstantiateMsg::<Empty> {
                         name: msg.name.clone(),
                         symbol: msg.symbol.clone(),
                         minter: MOCK_CONTRACT_ADDR.to_string(),
+                        soulbound: false,
+                        contract_metadata: None,
                     })
                     .unwrap(),
                     funds: vec![],
@@ -264,7 +381,8 @@ mod tests {
                 symbol: msg.symbol,
                 token_uri: msg.token_uri,
                 extension: None,
-                unused_token_id: 0
+                unused_token_id: 0,
+                dutch_auction: None,
             }
         );
     }
@@ -282,6 +400,7 @@ mod tests {
             cw20_address: Addr::unchecked(MOCK_CONTRACT_ADDR),
             token_uri: String::from("https://ipfs.io/ipfs/Q"),
             extension: None,
+            dutch_auction: None,
         };
 
         let info = mock_info("owner", &[]);
@@ -306,6 +425,7 @@ mod tests {
             cw20_address: Addr::unchecked(MOCK_CONTRACT_ADDR),
             token_uri: String::from("https://ipfs.io/ipfs/Q"),
             extension: None,
+            dutch_auction: None,
         };
 
         let info = mock_info("owner", &[]);
@@ -330,6 +450,7 @@ mod tests {
             cw20_address: Addr::unchecked(MOCK_CONTRACT_ADDR),
             token_uri: String::from("https://ipfs.io/ipfs/Q"),
             extension: None,
+            dutch_auction: None,
         };
 
         let info = mock_info("owner", &[]);
@@ -361,7 +482,7 @@ mod tests {
         let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
         let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-        let mint_msg = Cw721ExecuteMsg::Mint(MintMsg::<Extension> {
+        let mint_msg: Cw721ExecuteMsg<Extension> = Cw721ExecuteMsg::Mint(MintMsg {
             token_id: String::from("0"),
             owner: String::from("minter"),
             token_uri: Some(String::from("https://ipfs.io/ipfs/Q")),
@@ -396,6 +517,7 @@ mod tests {
             cw20_address: Addr::unchecked(MOCK_CONTRACT_ADDR),
             token_uri: String::from("https://ipfs.io/ipfs/Q"),
             extension: None,
+            dutch_auction: None,
         };
 
         let info = mock_info("owner", &[]);
@@ -437,6 +559,7 @@ mod tests {
             cw20_address: Addr::unchecked(MOCK_CONTRACT_ADDR),
             token_uri: String::from("https://ipfs.io/ipfs/Q"),
             extension: None,
+            dutch_auction: None,
         };
 
         let info = mock_info("owner", &[]);
@@ -480,6 +603,7 @@ mod tests {
             cw20_address: Addr::unchecked(MOCK_CONTRACT_ADDR),
             token_uri: String::from("https://ipfs.io/ipfs/Q"),
             extension: None,
+            dutch_auction: None,
         };
 
         let info = mock_info("owner", &[]);
@@ -533,6 +657,7 @@ mod tests {
             cw20_address: Addr::unchecked(MOCK_CONTRACT_ADDR),
             token_uri: String::from("https://ipfs.io/ipfs/Q"),
             extension: None,
+            dutch_auction: None,
         };
 
         let info = mock_info("owner", &[]);
@@ -566,6 +691,7 @@ mod tests {
             cw20_address: Addr::unchecked(MOCK_CONTRACT_ADDR),
             token_uri: String::from("https://ipfs.io/ipfs/Q"),
             extension: None,
+            dutch_auction: None,
         };
 
         let info = mock_info("owner", &[]);
@@ -619,6 +745,7 @@ mod tests {
             cw20_address: Addr::unchecked(MOCK_CONTRACT_ADDR),
             token_uri: String::from("https://ipfs.io/ipfs/Q"),
             extension: None,
+            dutch_auction: None,
         };
 
         let info = mock_info("owner", &[]);
@@ -658,4 +785,220 @@ mod tests {
             e => panic!("unexpected error: {}", e),
         }
     }
+
+    fn setup_dutch_auction(deps: DepsMut, max_tokens: u32, rebate: bool) -> DutchAuction {
+        let start_time = mock_env().block.time;
+        let auction = DutchAuction {
+            start_price: Uint128::new(100),
+            start_time,
+            end_time: start_time.plus_seconds(100),
+            rebate,
+        };
+        let msg = InstantiateMsg {
+            owner: Addr::unchecked("owner"),
+            max_tokens,
+            unit_price: Uint128::new(10),
+            name: String::from("SYNTH"),
+            symbol: String::from("SYNTH"),
+            token_code_id: 10u64,
+            cw20_address: Addr::unchecked(MOCK_CONTRACT_ADDR),
+            token_uri: String::from("https://ipfs.io/ipfs/Q"),
+            extension: None,
+            dutch_auction: Some(auction.clone()),
+        };
+
+        let info = mock_info("owner", &[]);
+        let mut deps = deps;
+        instantiate(deps.branch(), mock_env(), info, msg).unwrap();
+
+        let instantiate_reply = MsgInstantiateContractResponse {
+            contract_address: "nftcontract".to_string(),
+            data: vec![2u8; 32769],
+        };
+        let mut encoded_instantiate_reply =
+            Vec::<u8>::with_capacity(instantiate_reply.encoded_len());
+        instantiate_reply
+            .encode(&mut encoded_instantiate_reply)
+            .unwrap();
+
+        let reply_msg = Reply {
+            id: INSTANTIATE_TOKEN_REPLY_ID,
+            result: SubMsgResult::Ok(SubMsgExecutionResponse {
+                events: vec![],
+                data: Some(encoded_instantiate_reply.into()),
+            }),
+        };
+        reply(deps, mock_env(), reply_msg).unwrap();
+        auction
+    }
+
+    fn env_at(seconds_after_start: u64) -> Env {
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(seconds_after_start);
+        env
+    }
+
+    #[test]
+    fn invalid_auction_schedule() {
+        let mut deps = mock_dependencies();
+        let start_time = mock_env().block.time;
+        let msg = InstantiateMsg {
+            owner: Addr::unchecked("owner"),
+            max_tokens: 1,
+            unit_price: Uint128::new(10),
+            name: String::from("SYNTH"),
+            symbol: String::from("SYNTH"),
+            token_code_id: 10u64,
+            cw20_address: Addr::unchecked(MOCK_CONTRACT_ADDR),
+            token_uri: String::from("https://ipfs.io/ipfs/Q"),
+            extension: None,
+            dutch_auction: Some(DutchAuction {
+                start_price: Uint128::new(5),
+                start_time,
+                end_time: start_time.plus_seconds(100),
+                rebate: false,
+            }),
+        };
+
+        let info = mock_info("owner", &[]);
+        let err = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+
+        match err {
+            ContractError::InvalidAuctionSchedule {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn dutch_auction_price_decays() {
+        let mut deps = mock_dependencies();
+        setup_dutch_auction(deps.as_mut(), 1, false);
+
+        let expected = [(0, 100), (50, 55), (99, 11), (100, 10), (1000, 10)];
+        for (seconds, price) in expected {
+            let res = query(deps.as_ref(), env_at(seconds), QueryMsg::CurrentPrice {}).unwrap();
+            let res: CurrentPriceResponse = from_binary(&res).unwrap();
+            assert_eq!(res.price, Uint128::new(price));
+        }
+    }
+
+    #[test]
+    fn dutch_auction_mint() {
+        let mut deps = mock_dependencies();
+        setup_dutch_auction(deps.as_mut(), 2, false);
+        let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+
+        // cannot buy before the sale starts
+        let mut early_env = mock_env();
+        early_env.block.time = early_env.block.time.minus_seconds(1);
+        let msg = ExecuteMsg::Cw20ReceiveMsg {
+            sender: String::from("minter"),
+            amount: Uint128::new(100),
+        };
+        let err = execute(deps.as_mut(), early_env, info.clone(), msg).unwrap_err();
+        match err {
+            ContractError::SaleNotStarted {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // paying below the current price fails
+        let msg = ExecuteMsg::Cw20ReceiveMsg {
+            sender: String::from("minter"),
+            amount: Uint128::new(54),
+        };
+        let err = execute(deps.as_mut(), env_at(50), info.clone(), msg).unwrap_err();
+        match err {
+            ContractError::WrongPaymentAmount {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // overpayment is refunded
+        let msg = ExecuteMsg::Cw20ReceiveMsg {
+            sender: String::from("minter"),
+            amount: Uint128::new(60),
+        };
+        let res = execute(deps.as_mut(), env_at(50), info, msg).unwrap();
+        assert_eq!(res.messages.len(), 2);
+        assert_eq!(
+            res.messages[1],
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: String::from(MOCK_CONTRACT_ADDR),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: String::from("minter"),
+                    amount: Uint128::new(5),
+                })
+                .unwrap(),
+                funds: vec![],
+            })
+        );
+    }
+
+    #[test]
+    fn dutch_auction_rebate() {
+        let mut deps = mock_dependencies();
+        setup_dutch_auction(deps.as_mut(), 2, true);
+        let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+
+        // early buyer pays the start price
+        let msg = ExecuteMsg::Cw20ReceiveMsg {
+            sender: String::from("early"),
+            amount: Uint128::new(100),
+        };
+        execute(deps.as_mut(), env_at(0), info.clone(), msg).unwrap();
+
+        // not settled until sold out or ended
+        let err = execute(
+            deps.as_mut(),
+            env_at(10),
+            mock_info("early", &[]),
+            ExecuteMsg::ClaimRebate {},
+        )
+        .unwrap_err();
+        match err {
+            ContractError::AuctionNotSettled {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // last token clears at 55
+        let msg = ExecuteMsg::Cw20ReceiveMsg {
+            sender: String::from("late"),
+            amount: Uint128::new(55),
+        };
+        execute(deps.as_mut(), env_at(50), info, msg).unwrap();
+
+        let res = execute(
+            deps.as_mut(),
+            env_at(60),
+            mock_info("early", &[]),
+            ExecuteMsg::ClaimRebate {},
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages[0],
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: String::from(MOCK_CONTRACT_ADDR),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: String::from("early"),
+                    amount: Uint128::new(45),
+                })
+                .unwrap(),
+                funds: vec![],
+            })
+        );
+
+        // rebates can only be claimed once, and the clearing buyer has none
+        for buyer in ["early", "late"] {
+            let err = execute(
+                deps.as_mut(),
+                env_at(60),
+                mock_info(buyer, &[]),
+                ExecuteMsg::ClaimRebate {},
+            )
+            .unwrap_err();
+            match err {
+                ContractError::NoRebate {} => {}
+                e => panic!("unexpected error: {}", e),
+            }
+        }
+    }
 }
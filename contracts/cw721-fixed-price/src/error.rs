@@ -32,4 +32,19 @@ pub enum ContractError {
 
     #[error("Cw721AlreadyLinked")]
     Cw721AlreadyLinked {},
+
+    #[error("InvalidAuctionSchedule")]
+    InvalidAuctionSchedule {},
+
+    #[error("SaleNotStarted")]
+    SaleNotStarted {},
+
+    #[error("RebateNotEnabled")]
+    RebateNotEnabled {},
+
+    #[error("AuctionNotSettled")]
+    AuctionNotSettled {},
+
+    #[error("NoRebate")]
+    NoRebate {},
 }
@@ -1,3 +1,4 @@
+use crate::state::DutchAuction;
 use cosmwasm_std::{Addr, Uint128};
 use cw721_base::Extension;
 use schemars::JsonSchema;
@@ -14,18 +15,28 @@ pub struct InstantiateMsg {
     pub cw20_address: Addr,
     pub token_uri: String,
     pub extension: Extension,
+    /// Sell as a Dutch auction decaying down to `unit_price` instead of at a fixed price
+    pub dutch_auction: Option<DutchAuction>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
-    Cw20ReceiveMsg { sender: String, amount: Uint128 },
+    Cw20ReceiveMsg {
+        sender: String,
+        amount: Uint128,
+    },
+    /// Claim back the difference between what the sender paid and the clearing
+    /// price of a settled Dutch auction
+    ClaimRebate {},
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
     GetConfig {},
+    /// Returns the price a purchase would pay at the current block time
+    CurrentPrice {},
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -40,4 +51,10 @@ pub struct ConfigResponse {
     pub token_uri: String,
     pub extension: Extension,
     pub unused_token_id: u32,
+    pub dutch_auction: Option<DutchAuction>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CurrentPriceResponse {
+    pub price: Uint128,
 }
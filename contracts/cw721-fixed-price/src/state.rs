@@ -2,8 +2,8 @@ use cw721_base::Extension;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{Addr, Uint128};
-use cw_storage_plus::Item;
+use cosmwasm_std::{Addr, Timestamp, Uint128};
+use cw_storage_plus::{Item, Map};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
@@ -17,6 +17,46 @@ pub struct Config {
     pub token_uri: String,
     pub extension: Extension,
     pub unused_token_id: u32,
+    /// If set, the sale runs as a Dutch auction with `unit_price` as the floor
+    pub dutch_auction: Option<DutchAuction>,
+}
+
+/// Price schedule for a Dutch auction sale. The price decays linearly from
+/// `start_price` at `start_time` down to the floor (`Config::unit_price`) at `end_time`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DutchAuction {
+    pub start_price: Uint128,
+    pub start_time: Timestamp,
+    pub end_time: Timestamp,
+    /// If true, buyers can claim back the difference between what they paid
+    /// and the clearing price once the auction has settled
+    pub rebate: bool,
+}
+
+impl DutchAuction {
+    /// returns the price at the given time, never going below floor_price
+    pub fn price_at(&self, floor_price: Uint128, time: Timestamp) -> Uint128 {
+        if time <= self.start_time {
+            return self.start_price;
+        }
+        if time >= self.end_time {
+            return floor_price;
+        }
+        let elapsed = time.nanos() - self.start_time.nanos();
+        let duration = self.end_time.nanos() - self.start_time.nanos();
+        let decay = (self.start_price - floor_price).multiply_ratio(elapsed, duration);
+        self.start_price - decay
+    }
+}
+
+/// What a single buyer has paid so far in a Dutch auction with rebates
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct Purchase {
+    pub count: u32,
+    pub total_paid: Uint128,
 }
 
 pub const CONFIG: Item<Config> = Item::new("config");
+/// Price paid for the last token, set once the Dutch auction sells out
+pub const CLEARING_PRICE: Item<Uint128> = Item::new("clearing_price");
+pub const PURCHASES: Map<&Addr, Purchase> = Map::new("purchases");
@@ -2,7 +2,8 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use cosmwasm_std::Empty;
-pub use cw721_base::{ContractError, InstantiateMsg, MintMsg, MinterResponse, QueryMsg};
+use cw721_base::Attributes;
+pub use cw721_base::{ContractError, InstantiateMsg, MintMsg, MinterResponse, TraitQueryMsg};
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
 pub struct Trait {
@@ -25,17 +26,28 @@ pub struct Metadata {
     pub youtube_url: Option<String>,
 }
 
+impl Attributes for Metadata {
+    fn attributes(&self) -> Vec<(String, String)> {
+        self.attributes
+            .iter()
+            .flatten()
+            .map(|attribute| (attribute.trait_type.clone(), attribute.value.clone()))
+            .collect()
+    }
+}
+
 pub type Extension = Option<Metadata>;
 
 pub type Cw721MetadataContract<'a> = cw721_base::Cw721Contract<'a, Extension, Empty, Empty>;
 pub type ExecuteMsg = cw721_base::ExecuteMsg<Extension>;
+pub type QueryMsg = cw721_base::QueryMsg<TraitQueryMsg>;
 
 #[cfg(not(feature = "library"))]
 pub mod entry {
     use super::*;
 
     use cosmwasm_std::entry_point;
-    use cosmwasm_std::{Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
+    use cosmwasm_std::{Binary, Deps, DepsMut, Env, MessageInfo, Reply, Response, StdResult};
 
     // This is a simple type to let us handle empty extensions
 
@@ -57,12 +69,20 @@ pub mod entry {
         info: MessageInfo,
         msg: ExecuteMsg,
     ) -> Result<Response, ContractError> {
-        Cw721MetadataContract::default().execute(deps, env, info, msg)
+        Cw721MetadataContract::default().execute_indexed(deps, env, info, msg)
     }
 
     #[entry_point]
     pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
-        Cw721MetadataContract::default().query(deps, env, msg)
+        let contract = Cw721MetadataContract::default();
+        contract.query_with_extension(deps, env, msg, |deps, _env, msg| {
+            contract.query_traits(deps, msg)
+        })
+    }
+
+    #[entry_point]
+    pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+        Cw721MetadataContract::default().reply(deps, env, msg)
     }
 }
 
@@ -70,8 +90,10 @@ pub mod entry {
 mod tests {
     use super::*;
 
+    use cosmwasm_std::from_binary;
     use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
     use cw721::Cw721Query;
+    use cw721_base::{TraitCount, TraitCountsResponse};
 
     const CREATOR: &str = "creator";
 
@@ -85,6 +107,8 @@ mod tests {
             name: "SpaceShips".to_string(),
             symbol: "SPACE".to_string(),
             minter: CREATOR.to_string(),
+            soulbound: false,
+            contract_metadata: None,
         };
         contract
             .instantiate(deps.as_mut(), mock_env(), info.clone(), init_msg)
@@ -110,4 +134,100 @@ mod tests {
         assert_eq!(res.token_uri, mint_msg.token_uri);
         assert_eq!(res.extension, mint_msg.extension);
     }
+
+    #[test]
+    fn index_attributes() {
+        let mut deps = mock_dependencies();
+        let contract = Cw721MetadataContract::default();
+
+        let info = mock_info(CREATOR, &[]);
+        let init_msg = InstantiateMsg {
+            name: "Tickets".to_string(),
+            symbol: "TIX".to_string(),
+            minter: CREATOR.to_string(),
+            soulbound: false,
+            contract_metadata: None,
+        };
+        contract
+            .instantiate(deps.as_mut(), mock_env(), info.clone(), init_msg)
+            .unwrap();
+
+        let section = |value: &str| Trait {
+            display_type: None,
+            trait_type: "Section".to_string(),
+            value: value.to_string(),
+        };
+        for (token_id, value) in [("1", "VIP"), ("2", "Floor"), ("3", "VIP")] {
+            let mint_msg = MintMsg {
+                token_id: token_id.to_string(),
+                owner: "john".to_string(),
+                token_uri: None,
+                extension: Some(Metadata {
+                    attributes: Some(vec![section(value)]),
+                    ..Metadata::default()
+                }),
+            };
+            contract
+                .execute_indexed(
+                    deps.as_mut(),
+                    mock_env(),
+                    info.clone(),
+                    ExecuteMsg::Mint(mint_msg),
+                )
+                .unwrap();
+        }
+
+        let res = contract
+            .tokens_by_trait(
+                deps.as_ref(),
+                "Section".to_string(),
+                "VIP".to_string(),
+                None,
+                None,
+            )
+            .unwrap();
+        assert_eq!(res.tokens, vec!["1", "3"]);
+
+        // burned tokens leave the index
+        let burn_msg = ExecuteMsg::Burn {
+            token_id: "1".to_string(),
+        };
+        contract
+            .execute_indexed(deps.as_mut(), mock_env(), mock_info("john", &[]), burn_msg)
+            .unwrap();
+        let res = contract
+            .tokens_by_trait(
+                deps.as_ref(),
+                "Section".to_string(),
+                "VIP".to_string(),
+                None,
+                None,
+            )
+            .unwrap();
+        assert_eq!(res.tokens, vec!["3"]);
+
+        // the index is read through the extension queries
+        let query_msg = QueryMsg::Extension {
+            msg: TraitQueryMsg::TraitCounts {
+                trait_type: "Section".to_string(),
+                start_after: None,
+                limit: None,
+            },
+        };
+        let res: TraitCountsResponse =
+            from_binary(&entry::query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(
+            res.counts,
+            vec![
+                TraitCount {
+                    value: "Floor".to_string(),
+                    count: 1
+                },
+                TraitCount {
+                    value: "VIP".to_string(),
+                    count: 1
+                },
+            ]
+        );
+    }
 }
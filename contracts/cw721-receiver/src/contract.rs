@@ -0,0 +1,370 @@
+use crate::error::ContractError;
+use crate::msg::{DepositResponse, ExecuteMsg, InstantiateMsg, QueryMsg, ReceiveMsg};
+use crate::state::{Config, CONFIG, DEPOSITS};
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{
+    to_binary, Binary, Deps, DepsMut, Empty, Env, MessageInfo, Response, StdResult, WasmMsg,
+};
+use cw2::set_contract_version;
+use cw721::{AcceptsNftsResponse, Cw721ExecuteMsg, Cw721Receiver};
+
+// version info for migration info
+const CONTRACT_NAME: &str = "crates.io:cw721-receiver";
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Holds the NFTs sent to it until their owner withdraws them
+pub struct Escrow;
+
+impl Cw721Receiver<Empty> for Escrow {
+    type Err = ContractError;
+    type Msg = ReceiveMsg;
+
+    fn receive_nft(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        sender: String,
+        token_id: String,
+        msg: ReceiveMsg,
+    ) -> Result<Response, ContractError> {
+        // only the cw721 contract itself can vouch for the token
+        let config = CONFIG.load(deps.storage)?;
+        if !config.accepts(&info.sender) {
+            return Err(ContractError::UnknownCollection {});
+        }
+
+        match msg {
+            ReceiveMsg::Deposit { owner } => {
+                let owner = deps.api.addr_validate(&owner.unwrap_or(sender))?;
+                DEPOSITS.save(deps.storage, (&info.sender, &token_id), &owner)?;
+                Ok(Response::new()
+                    .add_attribute("action", "deposit")
+                    .add_attribute("collection", info.sender)
+                    .add_attribute("token_id", token_id)
+                    .add_attribute("owner", owner))
+            }
+        }
+    }
+
+    fn accepts_nfts(&self, deps: Deps, collection: String) -> StdResult<bool> {
+        let collection = deps.api.addr_validate(&collection)?;
+        Ok(CONFIG.load(deps.storage)?.accepts(&collection))
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    let collections = msg
+        .collections
+        .map(|collections| {
+            collections
+                .iter()
+                .map(|collection| deps.api.addr_validate(collection))
+                .collect::<StdResult<Vec<_>>>()
+        })
+        .transpose()?;
+    CONFIG.save(deps.storage, &Config { collections })?;
+
+    Ok(Response::new().add_attribute("action", "instantiate"))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    match msg {
+        ExecuteMsg::ReceiveNft(receive_msg) => {
+            Escrow.execute_receive_nft(deps, env, info, receive_msg)
+        }
+        ExecuteMsg::Withdraw {
+            collection,
+            token_id,
+        } => execute_withdraw(deps, info, collection, token_id),
+    }
+}
+
+pub fn execute_withdraw(
+    deps: DepsMut,
+    info: MessageInfo,
+    collection: String,
+    token_id: String,
+) -> Result<Response, ContractError> {
+    let collection = deps.api.addr_validate(&collection)?;
+    let key = (&collection, token_id.as_str());
+    let owner = DEPOSITS.load(deps.storage, key)?;
+    if owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    DEPOSITS.remove(deps.storage, key);
+
+    let transfer = WasmMsg::Execute {
+        contract_addr: collection.to_string(),
+        msg: to_binary(&Cw721ExecuteMsg::TransferNft {
+            recipient: owner.to_string(),
+            token_id: token_id.clone(),
+        })?,
+        funds: vec![],
+    };
+    Ok(Response::new()
+        .add_attribute("action", "withdraw")
+        .add_attribute("collection", collection)
+        .add_attribute("token_id", token_id)
+        .add_attribute("owner", owner)
+        .add_message(transfer))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Deposit {
+            collection,
+            token_id,
+        } => to_binary(&query_deposit(deps, collection, token_id)?),
+        QueryMsg::AcceptsNfts { collection } => to_binary(&AcceptsNftsResponse {
+            accepts: Escrow.accepts_nfts(deps, collection)?,
+        }),
+    }
+}
+
+fn query_deposit(deps: Deps, collection: String, token_id: String) -> StdResult<DepositResponse> {
+    let collection = deps.api.addr_validate(&collection)?;
+    let owner = DEPOSITS.load(deps.storage, (&collection, &token_id))?;
+    Ok(DepositResponse { owner })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::testing::{
+        mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MockStorage,
+    };
+    use cosmwasm_std::{from_binary, Addr, CosmosMsg, OwnedDeps, StdError};
+    use cw721::{Cw721Query, Cw721ReceiveMsg};
+    use cw721_base::{Cw721Contract, Extension, MintMsg};
+
+    const NFT: &str = "collection";
+    const ESCROW: &str = "escrow";
+    const MINTER: &str = "merlin";
+    const ALICE: &str = "alice";
+    const BOB: &str = "bob";
+
+    type Nft<'a> = Cw721Contract<'a, Extension, Empty, Empty>;
+
+    fn nft_env() -> Env {
+        let mut env = mock_env();
+        env.contract.address = Addr::unchecked(NFT);
+        env
+    }
+
+    fn escrow_env() -> Env {
+        let mut env = mock_env();
+        env.contract.address = Addr::unchecked(ESCROW);
+        env
+    }
+
+    /// A cw721 contract with one token owned by alice
+    fn setup_nft() -> OwnedDeps<MockStorage, MockApi, MockQuerier> {
+        let mut deps = mock_dependencies();
+        let contract = Nft::default();
+        let init_msg = cw721_base::InstantiateMsg {
+            name: "SpaceShips".to_string(),
+            symbol: "SPACE".to_string(),
+            minter: MINTER.to_string(),
+            soulbound: false,
+            contract_metadata: None,
+        };
+        contract
+            .instantiate(deps.as_mut(), nft_env(), mock_info(MINTER, &[]), init_msg)
+            .unwrap();
+        let mint_msg = cw721_base::ExecuteMsg::Mint(MintMsg {
+            token_id: "Enterprise".to_string(),
+            owner: ALICE.to_string(),
+            token_uri: None,
+            extension: None,
+        });
+        contract
+            .execute(deps.as_mut(), nft_env(), mock_info(MINTER, &[]), mint_msg)
+            .unwrap();
+        deps
+    }
+
+    fn setup_escrow(
+        collections: Option<Vec<String>>,
+    ) -> OwnedDeps<MockStorage, MockApi, MockQuerier> {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg { collections };
+        instantiate(deps.as_mut(), escrow_env(), mock_info(MINTER, &[]), msg).unwrap();
+        deps
+    }
+
+    /// Returns the execute message of the single wasm message in the response,
+    /// checking it is addressed to `contract`
+    fn single_execute<T: serde::de::DeserializeOwned>(res: &Response, contract: &str) -> T {
+        assert_eq!(res.messages.len(), 1);
+        match &res.messages[0].msg {
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr, msg, ..
+            }) => {
+                assert_eq!(contract_addr, contract);
+                from_binary(msg).unwrap()
+            }
+            msg => panic!("unexpected message: {:?}", msg),
+        }
+    }
+
+    #[test]
+    fn send_nft_round_trip() {
+        let mut nft = setup_nft();
+        let mut escrow = setup_escrow(Some(vec![NFT.to_string()]));
+
+        // alice sends the token to the escrow, for bob to withdraw
+        let send_msg = cw721_base::ExecuteMsg::SendNft {
+            contract: ESCROW.to_string(),
+            token_id: "Enterprise".to_string(),
+            msg: to_binary(&ReceiveMsg::Deposit {
+                owner: Some(BOB.to_string()),
+            })
+            .unwrap(),
+        };
+        let res = Nft::default()
+            .execute(nft.as_mut(), nft_env(), mock_info(ALICE, &[]), send_msg)
+            .unwrap();
+        let receive_msg: ExecuteMsg = single_execute(&res, ESCROW);
+        assert_eq!(
+            receive_msg,
+            ExecuteMsg::ReceiveNft(Cw721ReceiveMsg {
+                sender: ALICE.to_string(),
+                token_id: "Enterprise".to_string(),
+                msg: to_binary(&ReceiveMsg::Deposit {
+                    owner: Some(BOB.to_string()),
+                })
+                .unwrap(),
+            })
+        );
+        execute(
+            escrow.as_mut(),
+            escrow_env(),
+            mock_info(NFT, &[]),
+            receive_msg,
+        )
+        .unwrap();
+
+        let owner = Nft::default()
+            .owner_of(nft.as_ref(), nft_env(), "Enterprise".to_string(), false)
+            .unwrap();
+        assert_eq!(owner.owner, ESCROW);
+        let query_msg = QueryMsg::Deposit {
+            collection: NFT.to_string(),
+            token_id: "Enterprise".to_string(),
+        };
+        let res: DepositResponse =
+            from_binary(&query(escrow.as_ref(), escrow_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(res.owner, BOB);
+
+        // only bob can withdraw it, which hands it back through the cw721 contract
+        let withdraw_msg = ExecuteMsg::Withdraw {
+            collection: NFT.to_string(),
+            token_id: "Enterprise".to_string(),
+        };
+        let err = execute(
+            escrow.as_mut(),
+            escrow_env(),
+            mock_info(ALICE, &[]),
+            withdraw_msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let res = execute(
+            escrow.as_mut(),
+            escrow_env(),
+            mock_info(BOB, &[]),
+            withdraw_msg,
+        )
+        .unwrap();
+        let transfer_msg: cw721_base::ExecuteMsg<Extension> = single_execute(&res, NFT);
+        Nft::default()
+            .execute(
+                nft.as_mut(),
+                nft_env(),
+                mock_info(ESCROW, &[]),
+                transfer_msg,
+            )
+            .unwrap();
+
+        let owner = Nft::default()
+            .owner_of(nft.as_ref(), nft_env(), "Enterprise".to_string(), false)
+            .unwrap();
+        assert_eq!(owner.owner, BOB);
+        let query_msg = QueryMsg::Deposit {
+            collection: NFT.to_string(),
+            token_id: "Enterprise".to_string(),
+        };
+        query(escrow.as_ref(), escrow_env(), query_msg).unwrap_err();
+    }
+
+    #[test]
+    fn rejected_nfts() {
+        let mut escrow = setup_escrow(Some(vec![NFT.to_string()]));
+        let receive_msg = |msg: Binary| {
+            ExecuteMsg::ReceiveNft(Cw721ReceiveMsg {
+                sender: ALICE.to_string(),
+                token_id: "Enterprise".to_string(),
+                msg,
+            })
+        };
+        let deposit = to_binary(&ReceiveMsg::Deposit { owner: None }).unwrap();
+        let accepts_nfts = |escrow: &OwnedDeps<_, _, _>, collection: &str| {
+            let query_msg = QueryMsg::AcceptsNfts {
+                collection: collection.to_string(),
+            };
+            let res = query(escrow.as_ref(), escrow_env(), query_msg).unwrap();
+            from_binary::<AcceptsNftsResponse>(&res).unwrap().accepts
+        };
+        assert!(accepts_nfts(&escrow, NFT));
+        assert!(!accepts_nfts(&escrow, "other_collection"));
+        assert!(accepts_nfts(&setup_escrow(None), "other_collection"));
+
+        // tokens of other contracts are refused, which reverts the SendNft
+        let err = execute(
+            escrow.as_mut(),
+            escrow_env(),
+            mock_info("other_collection", &[]),
+            receive_msg(deposit.clone()),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::UnknownCollection {});
+
+        // and so are messages that don't decode
+        let err = execute(
+            escrow.as_mut(),
+            escrow_env(),
+            mock_info(NFT, &[]),
+            receive_msg(to_binary("deposit").unwrap()),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Std(StdError::ParseErr { .. })));
+
+        // the sender owns the deposit unless told otherwise
+        execute(
+            escrow.as_mut(),
+            escrow_env(),
+            mock_info(NFT, &[]),
+            receive_msg(deposit),
+        )
+        .unwrap();
+        let res = query_deposit(escrow.as_ref(), NFT.to_string(), "Enterprise".to_string());
+        assert_eq!(res.unwrap().owner, ALICE);
+    }
+}
@@ -0,0 +1,49 @@
+use cosmwasm_std::Addr;
+use cw721::Cw721ReceiveMsg;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct InstantiateMsg {
+    /// The cw721 contracts NFTs are accepted from, any if unset
+    pub collections: Option<Vec<String>>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecuteMsg {
+    /// Called by a cw721 contract when an NFT is sent here with `SendNft` or `SafeSendNft`
+    ReceiveNft(Cw721ReceiveMsg),
+    /// Sends a held NFT back to its owner, can only be called by the owner
+    Withdraw {
+        collection: String,
+        token_id: String,
+    },
+}
+
+/// The message attached to NFTs sent to this contract
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReceiveMsg {
+    /// Holds the NFT until its owner withdraws it. The owner is the sender if unset
+    Deposit { owner: Option<String> },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    /// Returns who can withdraw a held NFT, error if it isn't held
+    /// Return type: DepositResponse
+    Deposit {
+        collection: String,
+        token_id: String,
+    },
+    /// Tells `SafeSendNft` whether NFTs of `collection` are accepted
+    /// Return type: cw721::AcceptsNftsResponse
+    AcceptsNfts { collection: String },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DepositResponse {
+    pub owner: Addr,
+}
@@ -0,0 +1,24 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::Addr;
+use cw_storage_plus::{Item, Map};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Config {
+    /// The cw721 contracts NFTs are accepted from, any if unset
+    pub collections: Option<Vec<Addr>>,
+}
+
+impl Config {
+    pub fn accepts(&self, collection: &Addr) -> bool {
+        match &self.collections {
+            Some(collections) => collections.contains(collection),
+            None => true,
+        }
+    }
+}
+
+pub const CONFIG: Item<Config> = Item::new("config");
+/// Who can withdraw each held NFT, by cw721 contract and token id
+pub const DEPOSITS: Map<(&Addr, &str), Addr> = Map::new("deposits");
@@ -0,0 +1,5 @@
+fn main() {
+    // `mainnet` is passed through RUSTFLAGS, declare it so newer toolchains don't flag it.
+    // Older cargo ignores the key
+    println!("cargo:rustc-check-cfg=cfg(mainnet)");
+}
@@ -27,6 +27,39 @@ pub enum ContractError {
     #[error("No relevant funds present in transaction")]
     NoFundsPresent,
 
+    #[error("Token is being auctioned")]
+    TokenInAuction,
+
+    #[error("Auction end time must be in the future")]
+    InvalidAuctionEndTime,
+
+    #[error("Auction has ended")]
+    AuctionEnded,
+
+    #[error("Auction has not ended yet")]
+    AuctionNotEnded,
+
+    #[error("Bid must be at least {minimum}")]
+    BidTooLow { minimum: Uint64 },
+
+    #[error("Offer has expired")]
+    OfferExpired,
+
+    #[error("Listing expiration must be in the future")]
+    InvalidListExpiration,
+
+    #[error("Resale price must be at most {max}")]
+    ListPriceTooHigh { max: Uint64 },
+
+    #[error("Resale price must be at least {min}")]
+    ListPriceTooLow { min: Uint64 },
+
+    #[error("Not a cw721 base message")]
+    NotBaseMsg,
+
     #[error("{0}")]
     BaseError(#[from] cw721_base::ContractError),
+
+    #[error("{0}")]
+    RoyaltyError(#[from] cw2981_royalties::ContractError),
 }
@@ -1,14 +1,25 @@
 use crate::error::ContractError;
 use crate::error::ContractError::{LimitBelowLowestOffer, NoFundsPresent, NoListedTokensError};
-use crate::{Cw721SellableContract, Extension, DENOM_NAME};
-use cw721_base::ExecuteMsg;
+use crate::state::{
+    collection_offers, offers, Auction, Bid, CollectionOffer, Offer, Sale, AUCTIONS,
+    COLLECTION_OFFER_COUNT, MAX_SALE_HISTORY, SALE_COUNT, SALE_HISTORY,
+};
+use crate::{ContractMetadata, Cw721SellableContract, Extension, Metadata, DENOM_NAME};
+use cw2981_royalties::execute::validate_royalty;
+use cw2981_royalties::state::{record_resale, RESALES};
+use cw2981_royalties::MAX_BASIS_POINTS;
+use cw721::Expiration;
+use cw721_base::state::TokenInfo;
+use cw721_base::{Cw721Hook, TokenAction};
 
 use cosmwasm_std::{
-    Addr, BankMsg, Coin, Deps, DepsMut, Env, MessageInfo, Order, Response, StdError, Uint64,
+    Addr, BankMsg, BlockInfo, Coin, Deps, DepsMut, Empty, Env, Event, MessageInfo, Order, Response,
+    StdError, StdResult, Storage, Timestamp, Uint64,
 };
 use schemars::Map;
 
-pub fn try_buy(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+pub fn try_buy(mut deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+    check_not_paused(deps.storage)?;
     let maybe_coin = info.funds.iter().find(|&coin| coin.denom.eq(DENOM_NAME));
 
     if let Some(coin) = maybe_coin {
@@ -23,7 +34,7 @@ pub fn try_buy(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractErr
         {
             let opt_price = info.extension.as_ref().and_then(|meta| meta.list_price);
             let metadata = info.extension.ok_or(ContractError::NoMetadataPresent)?;
-            if !metadata.redeemed {
+            if !metadata.redeemed && !metadata.listing_expired(&env.block) {
                 if let Some(list_price) = opt_price {
                     if let Ok((_, _, lowest_price)) = lowest {
                         if list_price < lowest_price {
@@ -48,25 +59,18 @@ pub fn try_buy(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractErr
                 }
             })
             .and_then(|(lowest_token_id, lowest_token_owner, lowest_price)| {
-                contract.tokens.update::<_, ContractError>(
+                hand_over(deps.branch(), &lowest_token_id, &info.sender)?;
+
+                let token = contract.tokens.load(deps.storage, &lowest_token_id)?;
+                let mut messages = sale_payouts(
                     deps.storage,
-                    lowest_token_id.as_str(),
-                    |old| {
-                        let mut token_info = old.unwrap();
-                        let mut meta = token_info.extension.unwrap();
-                        meta.list_price = None;
-                        token_info.extension = Some(meta);
-                        token_info.owner = info.sender.clone();
-                        Ok(token_info)
-                    },
+                    &env.block,
+                    &lowest_token_id,
+                    &lowest_token_owner,
+                    token.extension.as_ref(),
+                    lowest_price,
                 )?;
-
-                let payment_coin = Coin::new(lowest_price.u64() as u128, DENOM_NAME);
                 let delta = limit - lowest_price;
-                let mut messages = vec![BankMsg::Send {
-                    to_address: lowest_token_owner.to_string(),
-                    amount: vec![payment_coin],
-                }];
                 if delta.u64() > 0 {
                     messages.push(BankMsg::Send {
                         to_address: info.sender.to_string(),
@@ -74,12 +78,22 @@ pub fn try_buy(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractErr
                     })
                 }
 
+                let sale = record_sale(
+                    deps.storage,
+                    &env,
+                    &lowest_token_id,
+                    &lowest_token_owner,
+                    &info.sender,
+                    lowest_price,
+                )?;
+
                 Ok(Response::new()
                     .add_attribute("method", "buy")
+                    .add_event(sale)
                     .add_messages(messages))
             })
     } else {
-        return Err(NoFundsPresent);
+        Err(NoFundsPresent)
     }
 }
 
@@ -88,38 +102,117 @@ pub fn try_list(
     env: Env,
     info: MessageInfo,
     listings: Map<String, Uint64>,
+    expires: Option<Expiration>,
 ) -> Result<Response, ContractError> {
+    check_not_paused(deps.storage)?;
+    if matches!(expires, Some(expires) if expires.is_expired(&env.block)) {
+        return Err(ContractError::InvalidListExpiration);
+    }
+
     let contract = Cw721SellableContract::default();
+    let minter = contract.minter.load(deps.storage)?;
+    let contract_metadata = contract
+        .contract_metadata
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    let mut events = vec![];
     for (token_id, price) in listings.iter() {
         check_can_send(deps.as_ref(), &env, &info, token_id)?;
-        contract
+        let token = contract
             .tokens
             .update::<_, ContractError>(deps.storage, token_id, |old| {
-                old.ok_or(StdError::not_found("SellableToken").into())
-                    .map(|mut old| {
-                        let opt_price = if (*price) > Uint64::new(0) {
-                            Some(*price)
-                        } else {
-                            None
-                        };
-                        // TODO: get rid of this unwrap
-                        let mut meta = old.extension.unwrap();
-                        meta.list_price = opt_price;
-                        old.extension = Some(meta);
-                        old
-                    })
+                let mut old = old.ok_or(StdError::not_found("SellableToken"))?;
+                let opt_price = if (*price) > Uint64::new(0) {
+                    Some(*price)
+                } else {
+                    None
+                };
+                // the minter's own listings are primary sales, not resales
+                if opt_price.is_some() && old.owner != minter {
+                    check_resale_price(&contract_metadata, *price)?;
+                }
+                // TODO: get rid of this unwrap
+                let mut meta = old.extension.unwrap();
+                meta.list_price = opt_price;
+                meta.list_expires = opt_price.and(expires);
+                old.extension = Some(meta);
+                Ok(old)
             })?;
+
+        let event = if (*price) > Uint64::new(0) {
+            let mut event = Event::new("list")
+                .add_attribute("token_id", token_id)
+                .add_attribute("seller", token.owner)
+                .add_attribute("price", *price);
+            if let Some(expires) = expires {
+                event = event.add_attribute("expires", expires.to_string());
+            }
+            event
+        } else {
+            Event::new("delist")
+                .add_attribute("token_id", token_id)
+                .add_attribute("seller", token.owner)
+        };
+        events.push(event);
+    }
+
+    Ok(Response::new()
+        .add_attribute("method", "list")
+        .add_events(events))
+}
+
+fn check_resale_price(
+    contract_metadata: &ContractMetadata,
+    price: Uint64,
+) -> Result<(), ContractError> {
+    if let Some(max) = contract_metadata.max_resale_price {
+        if price > max {
+            return Err(ContractError::ListPriceTooHigh { max });
+        }
+    }
+    if let Some(percentage) = contract_metadata.min_resale_percentage {
+        let min = contract_metadata
+            .initial_price
+            .multiply_ratio(percentage, 100_u64);
+        if price < min {
+            return Err(ContractError::ListPriceTooLow { min });
+        }
+    }
+    Ok(())
+}
+
+pub fn try_update_resale_rules(
+    deps: DepsMut,
+    info: MessageInfo,
+    max_resale_price: Option<Uint64>,
+    min_resale_percentage: Option<u64>,
+) -> Result<Response, ContractError> {
+    let contract = Cw721SellableContract::default();
+    let minter = contract.minter.load(deps.storage)?;
+    if info.sender != minter {
+        return Err(ContractError::Unauthorized);
     }
 
-    Ok(Response::new().add_attribute("method", "list"))
+    let mut contract_metadata = contract
+        .contract_metadata
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    contract_metadata.max_resale_price = max_resale_price;
+    contract_metadata.min_resale_percentage = min_resale_percentage;
+    contract
+        .contract_metadata
+        .save(deps.storage, &contract_metadata)?;
+
+    Ok(Response::new().add_attribute("method", "update_resale_rules"))
 }
 
 pub fn try_redeem(
     deps: DepsMut,
     info: MessageInfo,
     address: String,
-    ticket_id: &String,
+    ticket_id: &str,
 ) -> Result<Response, ContractError> {
+    check_not_paused(deps.storage)?;
     let contract = Cw721SellableContract::default();
 
     // Validate only contract owner can call method
@@ -130,6 +223,7 @@ pub fn try_redeem(
 
     // Load ticket, error if ticket does not exist
     let mut ticket = contract.tokens.load(deps.storage, ticket_id)?;
+    check_not_auctioned(deps.storage, ticket_id)?;
 
     // Make sure owner param matches ticket owner
     if ticket.owner != address {
@@ -148,6 +242,7 @@ pub fn try_redeem(
             metadata.locked = true;
             // de-list ticket if it is listed
             metadata.list_price = None;
+            metadata.list_expires = None;
         }
     } else {
         return Err(ContractError::NoMetadataPresent);
@@ -156,83 +251,558 @@ pub fn try_redeem(
     // Save change into storage
     contract.tokens.save(deps.storage, ticket_id, &ticket)?;
 
-    return Ok(Response::new().add_attribute("method", "redeem"));
+    Ok(Response::new().add_attribute("method", "redeem").add_event(
+        Event::new("redeem")
+            .add_attribute("token_id", ticket_id)
+            .add_attribute("owner", ticket.owner),
+    ))
 }
 
-// todo: is there a way to use the cw721 base function here?
 pub fn check_can_send(
     deps: Deps,
     env: &Env,
     info: &MessageInfo,
-    token_id: &String,
+    token_id: &str,
 ) -> Result<(), ContractError> {
     let contract = Cw721SellableContract::default();
-    let mut token = contract.tokens.load(deps.storage, token_id)?;
-    // confirm token aren't locked or redeemed
-    if let Some(ref mut metadata) = token.extension {
-        if metadata.redeemed {
-            return Err(ContractError::TicketRedeemed);
-        } else if metadata.locked {
-            return Err(ContractError::TicketLocked);
+    let token = contract.tokens.load(deps.storage, token_id)?;
+    check_not_auctioned(deps.storage, token_id)?;
+    if let Some(ref metadata) = token.extension {
+        check_ticket_movable(metadata)?;
+    }
+
+    contract
+        .check_can_send(deps, env, info, &token)
+        .map_err(|err| match err {
+            cw721_base::ContractError::Unauthorized {} => ContractError::Unauthorized,
+            err => err.into(),
+        })
+}
+
+/// Keeps locked, redeemed and auctioned tickets in place when handling base messages
+pub struct TicketHook;
+
+impl Cw721Hook<Extension, Empty, ContractError> for TicketHook {
+    fn before(
+        &self,
+        deps: Deps,
+        _env: &Env,
+        _info: &MessageInfo,
+        action: &TokenAction,
+        token: &TokenInfo<Extension>,
+    ) -> Result<(), ContractError> {
+        // auctioned tokens can't be burned either, as the highest bid is held for them
+        check_not_auctioned(deps.storage, action.token_id())?;
+        if let TokenAction::Burn { .. } = action {
+            return Ok(());
         }
+        let metadata = token
+            .extension
+            .as_ref()
+            .ok_or(ContractError::NoMetadataPresent)?;
+        check_ticket_movable(metadata)
     }
-    if token.owner == info.sender {
-        return Ok(());
+}
+
+/// confirm the ticket isn't locked or redeemed
+fn check_ticket_movable(metadata: &Metadata) -> Result<(), ContractError> {
+    if metadata.redeemed {
+        Err(ContractError::TicketRedeemed)
+    } else if metadata.locked {
+        Err(ContractError::TicketLocked)
+    } else {
+        Ok(())
+    }
+}
+
+/// royalties are held to the limits of cw2981 royalties
+pub fn validate_token_royalty(metadata: &Metadata) -> Result<(), ContractError> {
+    validate_royalty(
+        metadata.royalty_percentage,
+        metadata.royalty_basis_points,
+        metadata.royalty_schedule.as_deref().unwrap_or_default(),
+    )?;
+    Ok(())
+}
+
+/// the contract wide royalty is held to the same limits as the royalty of a token
+pub fn validate_contract_royalty(metadata: &ContractMetadata) -> Result<(), ContractError> {
+    validate_royalty(
+        Some(metadata.royalty.u64()),
+        metadata.royalty_basis_points,
+        metadata.royalty_schedule.as_deref().unwrap_or_default(),
+    )?;
+    Ok(())
+}
+
+/// the market stops along with the base contract when it is paused
+fn check_not_paused(storage: &dyn Storage) -> Result<(), ContractError> {
+    Cw721SellableContract::default().check_not_paused(storage)?;
+    Ok(())
+}
+
+fn check_not_auctioned(storage: &dyn Storage, token_id: &str) -> Result<(), ContractError> {
+    if AUCTIONS.has(storage, token_id) {
+        return Err(ContractError::TokenInAuction);
     }
+    Ok(())
+}
 
-    // any non-expired token approval can send
-    if token
-        .approvals
+/// returns the amount of DENOM_NAME sent with the transaction
+fn payment_amount(info: &MessageInfo) -> Result<Uint64, ContractError> {
+    info.funds
         .iter()
-        .any(|apr| apr.spender == info.sender && !apr.is_expired(&env.block))
-    {
-        return Ok(());
+        .find(|coin| coin.denom == DENOM_NAME && !coin.amount.is_zero())
+        .map(|coin| Uint64::new(coin.amount.u128() as u64))
+        .ok_or(NoFundsPresent)
+}
+
+/// builds the payments for a sale: the royalty set on the token, if any, and the rest to the seller
+fn sale_payouts(
+    storage: &dyn Storage,
+    block: &BlockInfo,
+    token_id: &str,
+    seller: &Addr,
+    metadata: Option<&Metadata>,
+    price: Uint64,
+) -> StdResult<Vec<BankMsg>> {
+    let resales = RESALES.may_load(storage, token_id)?.unwrap_or_default();
+    let mut payouts = vec![];
+    let mut seller_amount = price;
+    if let Some((bps, address)) = metadata.and_then(|metadata| {
+        metadata
+            .scheduled_royalty_bps(block, resales)
+            .zip(metadata.royalty_payment_address.as_ref())
+    }) {
+        let royalty = price.multiply_ratio(bps, MAX_BASIS_POINTS).min(price);
+        if !royalty.is_zero() {
+            payouts.push(BankMsg::Send {
+                to_address: address.clone(),
+                amount: vec![Coin::new(royalty.u64() as u128, DENOM_NAME)],
+            });
+            seller_amount -= royalty;
+        }
+    }
+    if !seller_amount.is_zero() {
+        payouts.push(BankMsg::Send {
+            to_address: seller.to_string(),
+            amount: vec![Coin::new(seller_amount.u64() as u128, DENOM_NAME)],
+        });
     }
+    Ok(payouts)
+}
 
-    // operator can send
-    contract
-        .operators
-        .may_load(deps.storage, (&token.owner, &info.sender))
-        .map_err(|e| e.into())
-        .and_then(|opt_exp| match opt_exp {
-            Some(opt) if !opt.is_expired(&env.block) => Ok(()),
-            _ => Err(ContractError::Unauthorized),
-        })
+pub fn try_create_auction(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    token_id: String,
+    reserve: Uint64,
+    min_increment: Uint64,
+    end_time: Timestamp,
+) -> Result<Response, ContractError> {
+    check_not_paused(deps.storage)?;
+    check_can_send(deps.as_ref(), &env, &info, &token_id)?;
+    if end_time <= env.block.time {
+        return Err(ContractError::InvalidAuctionEndTime);
+    }
+
+    // an auctioned token is no longer for sale at its list price
+    let contract = Cw721SellableContract::default();
+    let mut token = contract.tokens.load(deps.storage, &token_id)?;
+    contract.prune_approvals(deps.storage, &env.block, &token_id, &mut token);
+    if let Some(ref mut metadata) = token.extension {
+        metadata.list_price = None;
+        metadata.list_expires = None;
+    }
+    contract.tokens.save(deps.storage, &token_id, &token)?;
+
+    let auction = Auction {
+        seller: token.owner,
+        reserve,
+        min_increment,
+        end_time,
+        highest_bid: None,
+    };
+    AUCTIONS.save(deps.storage, &token_id, &auction)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "create_auction")
+        .add_attribute("token_id", token_id)
+        .add_attribute("reserve", reserve)
+        .add_attribute("end_time", end_time.to_string()))
 }
 
-fn get_ticket_id(msg: &ExecuteMsg<Extension>) -> Option<String> {
-    // get token id from msg
-    return match msg {
-        ExecuteMsg::TransferNft { token_id, .. } => Some(token_id.to_string()),
-        ExecuteMsg::SendNft { token_id, .. } => Some(token_id.to_string()),
-        ExecuteMsg::Approve { token_id, .. } => Some(token_id.to_string()),
-        ExecuteMsg::Revoke { token_id, .. } => Some(token_id.to_string()),
-        _ => None,
+pub fn try_bid(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    token_id: String,
+) -> Result<Response, ContractError> {
+    check_not_paused(deps.storage)?;
+    let amount = payment_amount(&info)?;
+    let mut auction = AUCTIONS.load(deps.storage, &token_id)?;
+    if env.block.time >= auction.end_time {
+        return Err(ContractError::AuctionEnded);
+    }
+    if info.sender == auction.seller {
+        return Err(ContractError::Unauthorized);
+    }
+    // only bidders that could receive the token can win it
+    Cw721SellableContract::default().check_recipient(deps.as_ref(), &info.sender)?;
+
+    let minimum = match &auction.highest_bid {
+        // always require a strictly higher bid
+        Some(bid) => bid.amount + auction.min_increment.max(Uint64::new(1)),
+        None => auction.reserve,
     };
+    if amount < minimum {
+        return Err(ContractError::BidTooLow { minimum });
+    }
+
+    let outbid = auction.highest_bid.replace(Bid {
+        bidder: info.sender.clone(),
+        amount,
+    });
+    AUCTIONS.save(deps.storage, &token_id, &auction)?;
+
+    let mut response = Response::new()
+        .add_attribute("method", "bid")
+        .add_attribute("token_id", token_id)
+        .add_attribute("bidder", info.sender)
+        .add_attribute("amount", amount);
+    // refund the previous highest bidder
+    if let Some(outbid) = outbid {
+        response = response.add_message(BankMsg::Send {
+            to_address: outbid.bidder.to_string(),
+            amount: vec![Coin::new(outbid.amount.u64() as u128, DENOM_NAME)],
+        });
+    }
+    Ok(response)
 }
 
-pub fn validate_locked_ticket(
-    deps: &DepsMut,
-    msg: &ExecuteMsg<Extension>,
-) -> Result<(), ContractError> {
-    let ticket_id = get_ticket_id(msg);
+pub fn try_settle_auction(
+    mut deps: DepsMut,
+    env: Env,
+    token_id: String,
+) -> Result<Response, ContractError> {
+    check_not_paused(deps.storage)?;
+    let auction = AUCTIONS.load(deps.storage, &token_id)?;
+    if env.block.time < auction.end_time {
+        return Err(ContractError::AuctionNotEnded);
+    }
+    AUCTIONS.remove(deps.storage, &token_id);
 
-    if let Some(ticket_id) = ticket_id {
-        let contract = Cw721SellableContract::default();
-        let ticket = contract.tokens.load(deps.storage, ticket_id.as_str())?;
-        // confirm token aren't locked or redeemed
-        if let Some(metadata) = ticket.extension {
-            if metadata.redeemed {
-                return Err(ContractError::TicketRedeemed);
-            } else if metadata.locked {
-                return Err(ContractError::TicketLocked);
-            } else {
-                return Ok(());
-            }
-        } else {
-            return Err(ContractError::NoMetadataPresent);
+    let response = Response::new()
+        .add_attribute("method", "settle_auction")
+        .add_attribute("token_id", token_id.clone());
+
+    // without bids the token simply stays with the seller
+    let bid = match auction.highest_bid {
+        Some(bid) => bid,
+        None => return Ok(response),
+    };
+
+    // the transfer restriction may have changed since the bid, rather than getting stuck
+    // the auction ends with the bid refunded and the token left with the seller
+    let contract = Cw721SellableContract::default();
+    match contract.check_recipient(deps.as_ref(), &bid.bidder) {
+        Err(cw721_base::ContractError::RecipientNotAllowed { .. }) => {
+            return Ok(response
+                .add_attribute("refunded", bid.bidder.clone())
+                .add_message(BankMsg::Send {
+                    to_address: bid.bidder.to_string(),
+                    amount: vec![Coin::new(bid.amount.u64() as u128, DENOM_NAME)],
+                }));
         }
-    } else {
-        return Ok(());
+        result => result?,
+    }
+
+    hand_over(deps.branch(), &token_id, &bid.bidder)?;
+    let token = contract.tokens.load(deps.storage, &token_id)?;
+    let payouts = sale_payouts(
+        deps.storage,
+        &env.block,
+        &token_id,
+        &auction.seller,
+        token.extension.as_ref(),
+        bid.amount,
+    )?;
+    let sale = record_sale(
+        deps.storage,
+        &env,
+        &token_id,
+        &auction.seller,
+        &bid.bidder,
+        bid.amount,
+    )?;
+
+    Ok(response
+        .add_attribute("buyer", bid.bidder)
+        .add_attribute("price", bid.amount)
+        .add_event(sale)
+        .add_messages(payouts))
+}
+
+pub fn try_make_offer(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    token_id: String,
+    expires: Option<Expiration>,
+) -> Result<Response, ContractError> {
+    check_not_paused(deps.storage)?;
+    let amount = payment_amount(&info)?;
+    let expires = expires.unwrap_or_default();
+    if expires.is_expired(&env.block) {
+        return Err(ContractError::OfferExpired);
+    }
+
+    let contract = Cw721SellableContract::default();
+    let token = contract.tokens.load(deps.storage, &token_id)?;
+    if token.owner == info.sender {
+        return Err(ContractError::Unauthorized);
+    }
+
+    let offer = Offer {
+        token_id: token_id.clone(),
+        bidder: info.sender.clone(),
+        amount,
+        expires,
+    };
+    let key = (token_id.as_str(), &info.sender);
+    let previous = offers().may_load(deps.storage, key)?;
+    offers().save(deps.storage, key, &offer)?;
+
+    let mut response = Response::new()
+        .add_attribute("method", "make_offer")
+        .add_attribute("token_id", token_id.clone())
+        .add_attribute("bidder", info.sender.clone())
+        .add_attribute("amount", amount);
+    // refund the offer this one replaces
+    if let Some(previous) = previous {
+        response = response.add_message(BankMsg::Send {
+            to_address: info.sender.to_string(),
+            amount: vec![Coin::new(previous.amount.u64() as u128, DENOM_NAME)],
+        });
     }
+    Ok(response)
+}
+
+pub fn try_accept_offer(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    token_id: String,
+    bidder: String,
+) -> Result<Response, ContractError> {
+    check_not_paused(deps.storage)?;
+    check_can_send(deps.as_ref(), &env, &info, &token_id)?;
+
+    let bidder = deps.api.addr_validate(&bidder)?;
+    let key = (token_id.as_str(), &bidder);
+    let offer = offers().load(deps.storage, key)?;
+    if offer.expires.is_expired(&env.block) {
+        return Err(ContractError::OfferExpired);
+    }
+    offers().remove(deps.storage, key)?;
+    let seller = hand_over(deps.branch(), &token_id, &bidder)?;
+    let token = Cw721SellableContract::default()
+        .tokens
+        .load(deps.storage, &token_id)?;
+    let payouts = sale_payouts(
+        deps.storage,
+        &env.block,
+        &token_id,
+        &seller,
+        token.extension.as_ref(),
+        offer.amount,
+    )?;
+    let sale = record_sale(
+        deps.storage,
+        &env,
+        &token_id,
+        &seller,
+        &bidder,
+        offer.amount,
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("method", "accept_offer")
+        .add_attribute("token_id", token_id)
+        .add_attribute("bidder", bidder)
+        .add_attribute("amount", offer.amount)
+        .add_event(sale)
+        .add_messages(payouts))
+}
+
+/// Moves a sold token to its buyer, dropping its approvals and listing.
+/// The buyer has to pass the transfer restriction like any other recipient.
+/// Returns the previous owner
+fn hand_over(deps: DepsMut, token_id: &str, buyer: &Addr) -> Result<Addr, ContractError> {
+    let contract = Cw721SellableContract::default();
+    contract.check_recipient(deps.as_ref(), buyer)?;
+    let mut token = contract.tokens.load(deps.storage, token_id)?;
+    let seller = token.owner.clone();
+    contract.set_owner(deps.storage, &mut token, buyer.clone())?;
+    contract.clear_approvals(deps.storage, token_id, &mut token);
+    if let Some(ref mut metadata) = token.extension {
+        metadata.list_price = None;
+        metadata.list_expires = None;
+    }
+    contract.tokens.save(deps.storage, token_id, &token)?;
+    Ok(seller)
+}
+
+/// Appends a sale to the token's history, dropping the oldest one once there
+/// are more than `MAX_SALE_HISTORY`, and returns the matching event
+fn record_sale(
+    storage: &mut dyn Storage,
+    env: &Env,
+    token_id: &str,
+    seller: &Addr,
+    buyer: &Addr,
+    price: Uint64,
+) -> StdResult<Event> {
+    let count = SALE_COUNT.may_load(storage, token_id)?.unwrap_or_default() + 1;
+    SALE_COUNT.save(storage, token_id, &count)?;
+    let sale = Sale {
+        seller: seller.clone(),
+        buyer: buyer.clone(),
+        price,
+        time: env.block.time,
+    };
+    SALE_HISTORY.save(storage, (token_id, count), &sale)?;
+    if count > MAX_SALE_HISTORY {
+        SALE_HISTORY.remove(storage, (token_id, count - MAX_SALE_HISTORY));
+    }
+    // the minter selling its own tickets is not a resale
+    if *seller != Cw721SellableContract::default().minter.load(storage)? {
+        record_resale(storage, token_id)?;
+    }
+
+    Ok(Event::new("sale")
+        .add_attribute("token_id", token_id)
+        .add_attribute("seller", seller)
+        .add_attribute("buyer", buyer)
+        .add_attribute("price", price))
+}
+
+pub fn try_withdraw_offer(
+    deps: DepsMut,
+    info: MessageInfo,
+    token_id: String,
+) -> Result<Response, ContractError> {
+    let key = (token_id.as_str(), &info.sender);
+    let offer = offers().load(deps.storage, key)?;
+    offers().remove(deps.storage, key)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "withdraw_offer")
+        .add_attribute("token_id", token_id)
+        .add_attribute("bidder", info.sender.clone())
+        .add_message(BankMsg::Send {
+            to_address: info.sender.to_string(),
+            amount: vec![Coin::new(offer.amount.u64() as u128, DENOM_NAME)],
+        }))
+}
+
+pub fn try_make_collection_offer(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    expires: Option<Expiration>,
+) -> Result<Response, ContractError> {
+    check_not_paused(deps.storage)?;
+    let amount = payment_amount(&info)?;
+    let expires = expires.unwrap_or_default();
+    if expires.is_expired(&env.block) {
+        return Err(ContractError::OfferExpired);
+    }
+
+    let id = COLLECTION_OFFER_COUNT
+        .may_load(deps.storage)?
+        .unwrap_or_default()
+        + 1;
+    COLLECTION_OFFER_COUNT.save(deps.storage, &id)?;
+    let offer = CollectionOffer {
+        id,
+        bidder: info.sender.clone(),
+        amount,
+        expires,
+    };
+    collection_offers().save(deps.storage, id, &offer)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "make_collection_offer")
+        .add_attribute("offer_id", id.to_string())
+        .add_attribute("bidder", info.sender)
+        .add_attribute("amount", amount))
+}
+
+pub fn try_fill_collection_offer(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    offer_id: u64,
+    token_id: String,
+) -> Result<Response, ContractError> {
+    check_not_paused(deps.storage)?;
+    check_can_send(deps.as_ref(), &env, &info, &token_id)?;
+
+    let offer = collection_offers().load(deps.storage, offer_id)?;
+    if offer.expires.is_expired(&env.block) {
+        return Err(ContractError::OfferExpired);
+    }
+    collection_offers().remove(deps.storage, offer_id)?;
+    let seller = hand_over(deps.branch(), &token_id, &offer.bidder)?;
+    let token = Cw721SellableContract::default()
+        .tokens
+        .load(deps.storage, &token_id)?;
+    let payouts = sale_payouts(
+        deps.storage,
+        &env.block,
+        &token_id,
+        &seller,
+        token.extension.as_ref(),
+        offer.amount,
+    )?;
+    let sale = record_sale(
+        deps.storage,
+        &env,
+        &token_id,
+        &seller,
+        &offer.bidder,
+        offer.amount,
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("method", "fill_collection_offer")
+        .add_attribute("offer_id", offer_id.to_string())
+        .add_attribute("token_id", token_id)
+        .add_attribute("bidder", offer.bidder)
+        .add_attribute("amount", offer.amount)
+        .add_event(sale)
+        .add_messages(payouts))
+}
+
+pub fn try_withdraw_collection_offer(
+    deps: DepsMut,
+    info: MessageInfo,
+    offer_id: u64,
+) -> Result<Response, ContractError> {
+    let offer = collection_offers().load(deps.storage, offer_id)?;
+    if offer.bidder != info.sender {
+        return Err(ContractError::Unauthorized);
+    }
+    collection_offers().remove(deps.storage, offer_id)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "withdraw_collection_offer")
+        .add_attribute("offer_id", offer_id.to_string())
+        .add_attribute("bidder", info.sender.clone())
+        .add_message(BankMsg::Send {
+            to_address: info.sender.to_string(),
+            amount: vec![Coin::new(offer.amount.u64() as u128, DENOM_NAME)],
+        }))
 }
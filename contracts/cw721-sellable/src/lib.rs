@@ -2,13 +2,16 @@ extern crate core;
 
 mod error;
 mod execute;
-mod msg;
-mod query;
+pub mod msg;
+pub mod query;
+pub mod state;
 mod test_utils;
 
-use crate::msg::{Cw721SellableExecuteMsg, InstantiateMsg};
-use cosmwasm_std::{Empty, Uint64};
-use cw2981_royalties::Trait;
+pub use crate::error::ContractError;
+use crate::msg::{Cw721SellableExecuteMsg, Cw721SellableQueryMsg, InstantiateMsg};
+use cosmwasm_std::{BlockInfo, Empty, Uint64};
+use cw2981_royalties::{RoyaltyStep, Trait};
+use cw721::Expiration;
 use cw721_base::Cw721Contract;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -38,11 +41,44 @@ pub struct Metadata {
     /// as the minter addr
     /// question: how do we validate this?
     pub royalty_payment_address: Option<String>,
+    /// The royalty in basis points, a hundredth of a percent.
+    /// Takes precedence over `royalty_percentage`
+    pub royalty_basis_points: Option<u64>,
+    /// Changes the royalty over time or as the ticket is resold
+    pub royalty_schedule: Option<Vec<RoyaltyStep>>,
     pub list_price: Option<Uint64>,
+    /// The listing can no longer be bought after this
+    pub list_expires: Option<Expiration>,
     pub locked: bool,
     pub redeemed: bool,
 }
 
+impl Metadata {
+    /// The royalty owed on a sale in basis points, if any
+    pub fn royalty_bps(&self) -> Option<u64> {
+        cw2981_royalties::royalty_bps(self.royalty_percentage, self.royalty_basis_points)
+    }
+
+    /// The royalty owed on a sale in basis points, following the royalty schedule if any
+    pub fn scheduled_royalty_bps(&self, block: &BlockInfo, resales: u64) -> Option<u64> {
+        match &self.royalty_schedule {
+            Some(schedule) if !schedule.is_empty() => Some(cw2981_royalties::scheduled_bps(
+                self.royalty_bps().unwrap_or_default(),
+                schedule,
+                block,
+                resales,
+            )),
+            _ => self.royalty_bps(),
+        }
+    }
+
+    /// True if the token has a list price that can no longer be bought at
+    pub fn listing_expired(&self, block: &BlockInfo) -> bool {
+        self.list_price.is_some()
+            && matches!(self.list_expires, Some(expires) if expires.is_expired(block))
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 pub struct Sponsor {
     pub id: String,
@@ -55,8 +91,16 @@ pub struct ContractMetadata {
     pub token_uri: Option<String>,
     pub initial_price: Uint64,
     pub royalty: Uint64,
+    /// The royalty on every ticket in basis points, takes precedence over `royalty`
+    pub royalty_basis_points: Option<u64>,
+    /// Changes the royalty on every ticket over time or as it is resold
+    pub royalty_schedule: Option<Vec<RoyaltyStep>>,
     pub num_of_tickets: Uint64,
     pub sponsors: Vec<Sponsor>,
+    /// Highest price a ticket can be resold at
+    pub max_resale_price: Option<Uint64>,
+    /// Lowest price a ticket can be resold at, as a percentage of `initial_price`
+    pub min_resale_percentage: Option<u64>,
 }
 
 pub type Extension = Option<Metadata>;
@@ -66,19 +110,28 @@ pub type MintExtension = Option<Extension>;
 pub type Cw721SellableContract<'a> = Cw721Contract<'a, Extension, Empty, ContractMetadata>;
 
 pub type ExecuteMsg = Cw721SellableExecuteMsg<Extension>;
+pub type QueryMsg = cw721_base::QueryMsg<Cw721SellableQueryMsg>;
 
 // #[cfg(not(feature = "library"))]
-mod entry {
+pub mod entry {
     use std::collections::BTreeMap;
 
     use super::*;
 
     use crate::error::ContractError;
-    use crate::execute::{try_buy, try_list, try_redeem, validate_locked_ticket};
+    use crate::execute::{
+        try_accept_offer, try_bid, try_buy, try_create_auction, try_fill_collection_offer,
+        try_list, try_make_collection_offer, try_make_offer, try_redeem, try_settle_auction,
+        try_update_resale_rules, try_withdraw_collection_offer, try_withdraw_offer,
+        validate_contract_royalty, validate_token_royalty, TicketHook,
+    };
     use crate::msg::{Cw721SellableExecuteMsg, Cw721SellableQueryMsg};
-    use crate::query::listed_tokens;
+    use crate::query::{
+        auction, auctions, collection_offers_by_price, listed_tokens, offers_by_bidder,
+        offers_by_token, sale_history,
+    };
     use cosmwasm_std::{entry_point, to_binary};
-    use cosmwasm_std::{Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
+    use cosmwasm_std::{Binary, Deps, DepsMut, Env, MessageInfo, Reply, Response, StdResult};
 
     #[entry_point]
     pub fn instantiate(
@@ -86,7 +139,8 @@ mod entry {
         env: Env,
         info: MessageInfo,
         msg: InstantiateMsg,
-    ) -> StdResult<Response> {
+    ) -> Result<Response, ContractError> {
+        validate_contract_royalty(&msg.contract_metadata)?;
         let contract = Cw721SellableContract::default();
 
         let mut heap_deps = Box::new(deps);
@@ -96,9 +150,6 @@ mod entry {
             info.clone(),
             msg.clone().into(),
         )?;
-        contract
-            .contract_metadata
-            .save(heap_deps.storage, &msg.contract_metadata)?;
 
         // Save a map of token ids to their list price
         let mut tokens_to_list: BTreeMap<String, Uint64> = BTreeMap::new();
@@ -111,7 +162,9 @@ mod entry {
                 extension: Some(Metadata {
                     description: Some(msg.contract_metadata.description.clone()),
                     name: Some(msg.name.clone()),
-                    royalty_percentage: Some(msg.contract_metadata.royalty.clone().into()),
+                    royalty_percentage: Some(msg.contract_metadata.royalty.into()),
+                    royalty_basis_points: msg.contract_metadata.royalty_basis_points,
+                    royalty_schedule: msg.contract_metadata.royalty_schedule.clone(),
                     ..Metadata::default()
                 }),
             };
@@ -121,20 +174,51 @@ mod entry {
             tokens_to_list.insert(n.to_string(), msg.contract_metadata.initial_price);
         }
         // List all tokens
-        try_list(heap_deps.branch(), env, info, tokens_to_list)
+        try_list(heap_deps.branch(), env, info, tokens_to_list, None)
             .unwrap_or_else(|_e| Response::default());
         Ok(Response::default())
     }
 
     #[entry_point]
-    pub fn query(deps: Deps, env: Env, msg: Cw721SellableQueryMsg) -> StdResult<Binary> {
+    pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+        Cw721SellableContract::default().query_with_extension(deps, env, msg, query_extension)
+    }
+
+    fn query_extension(deps: Deps, env: Env, msg: Cw721SellableQueryMsg) -> StdResult<Binary> {
         match msg {
             Cw721SellableQueryMsg::ListedTokens { limit, start_after } => {
-                to_binary(&listed_tokens(deps, start_after, limit)?)
+                to_binary(&listed_tokens(deps, env, start_after, limit)?)
+            }
+            Cw721SellableQueryMsg::Auction { token_id } => to_binary(&auction(deps, token_id)?),
+            Cw721SellableQueryMsg::Auctions { start_after, limit } => {
+                to_binary(&auctions(deps, start_after, limit)?)
             }
-            Cw721SellableQueryMsg::Cw2981Query(cw2981_msg) => Cw721SellableContract::default()
-                .query(deps, env, cw2981_msg.into())
-                .map_err(|e| e.into()),
+            Cw721SellableQueryMsg::OffersByToken {
+                token_id,
+                start_after,
+                limit,
+            } => to_binary(&offers_by_token(deps, token_id, start_after, limit)?),
+            Cw721SellableQueryMsg::OffersByBidder {
+                bidder,
+                start_after,
+                limit,
+            } => to_binary(&offers_by_bidder(deps, bidder, start_after, limit)?),
+            Cw721SellableQueryMsg::CollectionOffers {
+                start_after,
+                limit,
+                include_expired,
+            } => to_binary(&collection_offers_by_price(
+                deps,
+                env,
+                start_after,
+                limit,
+                include_expired.unwrap_or(false),
+            )?),
+            Cw721SellableQueryMsg::SaleHistory {
+                token_id,
+                start_after,
+                limit,
+            } => to_binary(&sale_history(deps, token_id, start_after, limit)?),
         }
     }
 
@@ -147,32 +231,73 @@ mod entry {
     ) -> Result<Response, ContractError> {
         use Cw721SellableExecuteMsg::*;
         match msg {
-            List { listings } => try_list(deps, env, info, listings),
-            Buy {} => try_buy(deps, info),
+            List { listings, expires } => try_list(deps, env, info, listings, expires),
+            Buy {} => try_buy(deps, env, info),
             RedeemTicket { address, ticket_id } => try_redeem(deps, info, address, &ticket_id),
+            CreateAuction {
+                token_id,
+                reserve,
+                min_increment,
+                end_time,
+            } => try_create_auction(deps, env, info, token_id, reserve, min_increment, end_time),
+            Bid { token_id } => try_bid(deps, env, info, token_id),
+            SettleAuction { token_id } => try_settle_auction(deps, env, token_id),
+            MakeOffer { token_id, expires } => try_make_offer(deps, env, info, token_id, expires),
+            AcceptOffer { token_id, bidder } => try_accept_offer(deps, env, info, token_id, bidder),
+            WithdrawOffer { token_id } => try_withdraw_offer(deps, info, token_id),
+            MakeCollectionOffer { expires } => try_make_collection_offer(deps, env, info, expires),
+            FillCollectionOffer { offer_id, token_id } => {
+                try_fill_collection_offer(deps, env, info, offer_id, token_id)
+            }
+            WithdrawCollectionOffer { offer_id } => {
+                try_withdraw_collection_offer(deps, info, offer_id)
+            }
+            UpdateResaleRules {
+                max_resale_price,
+                min_resale_percentage,
+            } => try_update_resale_rules(deps, info, max_resale_price, min_resale_percentage),
             BaseMsg(base_msg) => {
-                validate_locked_ticket(&deps, &base_msg)?;
-                Cw721SellableContract::default()
-                    .execute(deps, env, info, base_msg)
-                    .map_err(|x| x.into())
+                match &base_msg {
+                    cw721_base::ExecuteMsg::Mint(cw721_base::MintMsg {
+                        extension: Some(metadata),
+                        ..
+                    }) => validate_token_royalty(metadata)?,
+                    cw721_base::ExecuteMsg::UpdateContractMetadata(metadata) => {
+                        validate_contract_royalty(metadata)?
+                    }
+                    _ => {}
+                }
+                Cw721SellableContract::default().execute_with_hooks(
+                    deps,
+                    env,
+                    info,
+                    base_msg,
+                    &[&TicketHook],
+                )
             }
         }
     }
+
+    #[entry_point]
+    pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+        Ok(Cw721SellableContract::default().reply(deps, env, msg)?)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::entry::{execute, instantiate, query};
+    use crate::entry::{instantiate, query};
     use crate::error::ContractError;
-    use crate::test_utils::test_utils::{Context, ContractInfo};
-    use cosmwasm_std::{from_binary, to_binary, Addr, BankMsg, Coin, CosmosMsg, MessageInfo};
+    use crate::test_utils::{Context, ContractInfo};
+    use cosmwasm_std::{
+        from_binary, to_binary, Addr, BankMsg, Coin, CosmosMsg, Event, MessageInfo,
+    };
 
     use crate::msg::Cw721SellableQueryMsg;
     use crate::query::ListedTokensResponse;
     use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
-    use cw721::Cw721Query;
-    use cw721::NftInfoResponse;
+    use cw721::{Cw721Query, Expiration};
     use schemars::Map;
 
     const CREATOR: &str = "creator";
@@ -212,14 +337,15 @@ mod tests {
 
         let owner_info = mock_info(OWNER, &[]);
         let list_msg = Cw721SellableExecuteMsg::List {
-            listings: Map::from([("Voyager".to_string(), Uint64::from(30 as u8))]),
+            listings: Map::from([("Voyager".to_string(), Uint64::from(30_u8))]),
+            expires: None,
         };
         let exec_res = context.execute(owner_info.clone(), list_msg);
         exec_res.expect("expected list call to be successful");
 
         let mut query_res: ListedTokensResponse = context.query(query_msg.clone()).unwrap();
         assert_eq!(1, query_res.tokens.len());
-        let (listed_token_id, listed_token_info) = query_res.tokens.get(0).unwrap();
+        let (listed_token_id, listed_token_info) = query_res.tokens.first().unwrap();
         assert_eq!(
             listed_token_info
                 .extension
@@ -227,7 +353,7 @@ mod tests {
                 .unwrap()
                 .list_price
                 .unwrap(),
-            Uint64::from(30 as u8),
+            Uint64::from(30_u8),
             "listed token price did not match expectation"
         );
         assert_eq!(
@@ -238,6 +364,7 @@ mod tests {
 
         let delist_msg = Cw721SellableExecuteMsg::List {
             listings: Map::from([("Voyager".to_string(), Uint64::zero())]),
+            expires: None,
         };
         context
             .execute(owner_info.clone(), delist_msg)
@@ -292,11 +419,20 @@ mod tests {
         // List a token
         let owner_info = mock_info(OWNER, &[]);
         let list_msg = Cw721SellableExecuteMsg::List {
-            listings: Map::from([("Voyager".to_string(), Uint64::from(30 as u64))]),
+            listings: Map::from([("Voyager".to_string(), Uint64::from(30_u64))]),
+            expires: None,
         };
         context
             .execute(owner_info.clone(), list_msg)
             .expect("expected list call to be successful");
+        let approve_msg = ExecuteMsg::BaseMsg(cw721_base::ExecuteMsg::Approve {
+            spender: NO_MONEY.to_string(),
+            token_id: "Voyager".to_string(),
+            expires: None,
+        });
+        context
+            .execute(owner_info.clone(), approve_msg)
+            .expect("expected approve to succeed");
 
         // Buy a token
         let buyer_info_below_list = create_buy_info(BUYER, 20);
@@ -323,15 +459,27 @@ mod tests {
             "expected one message in response"
         );
 
-        let message = &response.messages.get(0).unwrap().msg;
+        let message = &response.messages.first().unwrap().msg;
         match message {
             CosmosMsg::Bank(BankMsg::Send { to_address, amount })
-                if to_address.eq(OWNER)
-                    && amount == &Vec::from([Coin::new(30 as u128, "uturnt")]) =>
-            {
-                assert!(true)
-            }
-            _ => assert!(false),
+                if to_address.eq(OWNER) && amount == &Vec::from([Coin::new(30, "uturnt")]) => {}
+            m => panic!("unexpected message: {:?}", m),
+        }
+
+        // approvals of the seller don't carry over to the buyer
+        let token = context
+            .contract
+            .tokens
+            .load(&context.deps.storage, "Voyager")
+            .unwrap();
+        assert_eq!(token.owner, BUYER);
+        assert!(token.approvals.is_empty());
+        for (holder, balance) in [(OWNER, 1), (BUYER, 1)] {
+            let res = context
+                .contract
+                .balance_of(&context.deps.storage, &Addr::unchecked(holder))
+                .unwrap();
+            assert_eq!(res, balance);
         }
     }
 
@@ -377,9 +525,10 @@ mod tests {
         let owner_info = mock_info(OWNER, &[]);
         let list_msg = Cw721SellableExecuteMsg::List {
             listings: Map::from([
-                ("Enterprise".to_string(), Uint64::from(31 as u64)),
-                ("Bullock".to_string(), Uint64::from(30 as u64)),
+                ("Enterprise".to_string(), Uint64::from(31_u64)),
+                ("Bullock".to_string(), Uint64::from(30_u64)),
             ]),
+            expires: None,
         };
         context
             .execute(owner_info.clone(), list_msg)
@@ -404,14 +553,11 @@ mod tests {
             .execute(buyer_info_at_list.clone(), Cw721SellableExecuteMsg::Buy {})
             .expect("expected buy at list price to succeed");
 
-        let message = &response.messages.get(0).unwrap().msg;
+        let message = &response.messages.first().unwrap().msg;
         match message {
             CosmosMsg::Bank(BankMsg::Send { to_address, amount })
-                if to_address.eq(OWNER) && amount == &Vec::from([Coin::new(30, "uturnt")]) =>
-            {
-                assert!(true)
-            }
-            _ => assert!(false),
+                if to_address.eq(OWNER) && amount == &Vec::from([Coin::new(30, "uturnt")]) => {}
+            m => panic!("unexpected message: {:?}", m),
         }
 
         let enterprise_info = context
@@ -440,10 +586,7 @@ mod tests {
             ticket_id: String::from("OWNER_TICKET"),
         };
         let exec_res = context.execute(mock_info(OWNER, &[]), msg).err();
-        match exec_res {
-            Some(ContractError::Unauthorized) => assert!(true),
-            _ => assert!(false),
-        };
+        assert!(matches!(exec_res, Some(ContractError::Unauthorized)));
 
         // Throw Error if ticket does exists in the contract
         let msg = Cw721SellableExecuteMsg::RedeemTicket {
@@ -451,10 +594,7 @@ mod tests {
             ticket_id: String::from("OWNER_TICKET"),
         };
         let exec_res = context.execute(mock_info(CREATOR, &[]), msg).err();
-        match exec_res {
-            None => assert!(false),
-            _ => assert!(true),
-        };
+        assert!(exec_res.is_some());
 
         // Make sure the owner param is the same as ticket owner in contract
         let token_id = "Burnt_Event#1";
@@ -476,10 +616,7 @@ mod tests {
             ticket_id: String::from("Burnt_Event#1"),
         };
         let exec_res = context.execute(mock_info(CREATOR, &[]), msg).err();
-        match exec_res {
-            Some(ContractError::Unauthorized) => assert!(true),
-            _ => assert!(false),
-        };
+        assert!(matches!(exec_res, Some(ContractError::Unauthorized)));
 
         // Make sure the ticket is not locked  or redeemed
         let locked_token_id = "Burnt_Locked#1";
@@ -502,10 +639,7 @@ mod tests {
             ticket_id: String::from("Burnt_Locked#1"),
         };
         let exec_res = context.execute(mock_info(CREATOR, &[]), msg).err();
-        match exec_res {
-            Some(ContractError::TicketLocked) => assert!(true),
-            _ => assert!(false),
-        };
+        assert!(matches!(exec_res, Some(ContractError::TicketLocked)));
 
         // Make sure the ticket metadata is updated
         let token_id = "Burnt_Event#2";
@@ -535,20 +669,8 @@ mod tests {
         let res = contract
             .nft_info(context.deps.as_ref(), token_id.to_string())
             .unwrap();
-        match res {
-            NftInfoResponse::<Extension> {
-                token_uri,
-                extension,
-            } => {
-                let metadata = extension.unwrap();
-                if metadata.redeemed {
-                    assert!(true);
-                } else {
-                    assert!(false);
-                }
-            }
-            _ => assert!(false),
-        }
+        let metadata = res.extension.unwrap();
+        assert!(metadata.redeemed);
     }
 
     #[test]
@@ -582,16 +704,15 @@ mod tests {
 
         let owner_info = mock_info(OWNER, &[]);
         let list_msg = Cw721SellableExecuteMsg::List {
-            listings: Map::from([(locked_token_id.to_string(), Uint64::from(30 as u64))]),
+            listings: Map::from([(locked_token_id.to_string(), Uint64::from(30_u64))]),
+            expires: None,
         };
         let res = context.execute(owner_info.clone(), list_msg).err();
-        match res {
-            Some(ContractError::TicketLocked) => assert!(true),
-            _ => {
-                println!("{:?}", res);
-                assert!(false)
-            }
-        };
+        assert!(
+            matches!(res, Some(ContractError::TicketLocked)),
+            "unexpected result: {:?}",
+            res
+        );
 
         // Make sure listed locked tickets are de-listed after redeeming
         let locked_token_id = "Burnt_Locked#2";
@@ -615,7 +736,8 @@ mod tests {
         // List a token
         let owner_info = mock_info(OWNER, &[]);
         let list_msg = Cw721SellableExecuteMsg::List {
-            listings: Map::from([(locked_token_id.to_string(), Uint64::from(30 as u64))]),
+            listings: Map::from([(locked_token_id.to_string(), Uint64::from(30_u64))]),
+            expires: None,
         };
         context
             .execute(owner_info.clone(), list_msg)
@@ -634,19 +756,8 @@ mod tests {
         let res = contract
             .nft_info(context.deps.as_ref(), locked_token_id.to_string())
             .unwrap();
-        match res {
-            NftInfoResponse::<Extension> {
-                token_uri: _,
-                extension,
-            } => {
-                let metadata = extension.unwrap_or(Metadata::default());
-                if metadata.redeemed {
-                    assert!(true);
-                } else {
-                    assert!(false);
-                }
-            }
-        };
+        let metadata = res.extension.unwrap_or_default();
+        assert!(metadata.redeemed);
     }
 
     #[test]
@@ -664,7 +775,7 @@ mod tests {
             minter: CREATOR.to_string(),
             contract_metadata: ContractMetadata {
                 description: "Ticketing for the Burnt event".to_string(),
-                num_of_tickets: Uint64::from(2 as u64),
+                num_of_tickets: Uint64::from(2_u64),
                 ..ContractMetadata::default()
             },
         };
@@ -717,13 +828,11 @@ mod tests {
             token_id: locked_token_id.to_string(),
         });
         let res = context.execute(owner_info.clone(), transfer_msg).err();
-        match res {
-            Some(ContractError::TicketLocked) => assert!(true),
-            _ => {
-                println!("{:?}", res);
-                assert!(false)
-            }
-        };
+        assert!(
+            matches!(res, Some(ContractError::TicketLocked)),
+            "unexpected result: {:?}",
+            res
+        );
 
         // Make sure send is not possible on locked ticket
         let owner_info = mock_info(OWNER, &[]);
@@ -733,13 +842,11 @@ mod tests {
             msg: to_binary(&vec![1, 2, 3]).unwrap(),
         });
         let res = context.execute(owner_info.clone(), send_msg).err();
-        match res {
-            Some(ContractError::TicketLocked) => assert!(true),
-            _ => {
-                println!("{:?}", res);
-                assert!(false)
-            }
-        };
+        assert!(
+            matches!(res, Some(ContractError::TicketLocked)),
+            "unexpected result: {:?}",
+            res
+        );
     }
 
     #[test]
@@ -754,8 +861,8 @@ mod tests {
             minter: CREATOR.to_string(),
             contract_metadata: ContractMetadata {
                 description: "Ticketing for the Burnt event".to_string(),
-                num_of_tickets: Uint64::from(2 as u64),
-                initial_price: Uint64::from(20 as u64),
+                num_of_tickets: Uint64::from(2_u64),
+                initial_price: Uint64::from(20_u64),
                 ..ContractMetadata::default()
             },
         };
@@ -768,8 +875,1175 @@ mod tests {
             limit: None,
         };
         let query_res: ListedTokensResponse =
-            from_binary(&query(deps.as_ref(), mock_env(), query_msg.clone()).unwrap()).unwrap();
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg.into()).unwrap()).unwrap();
         // Make sure all tickets were listed
         assert_eq!(2, query_res.tokens.len());
+
+        // The cw721 queries are answered alongside the sellable ones
+        let query_res: cw721::NumTokensResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::NumTokens {}).unwrap())
+                .unwrap();
+        assert_eq!(2, query_res.count);
+    }
+
+    /// Mints a token to the owner that pays a 10% royalty to the creator
+    fn mint_with_royalty(context: &mut Context, token_id: &str) {
+        let metadata = Metadata {
+            description: Some("Spaceship with Warp Drive".into()),
+            name: Some(format!("Starship USS {}", token_id)),
+            royalty_percentage: Some(10),
+            royalty_payment_address: Some(CREATOR.to_string()),
+            ..Metadata::default()
+        };
+        context
+            .mint(CREATOR, token_id, OWNER, metadata)
+            .expect("expected mint to succeed");
+    }
+
+    #[test]
+    fn auction_token() {
+        let mut context = Context::default();
+        mint_with_royalty(&mut context, "Enterprise");
+
+        // List it first, creating the auction takes it off the market
+        let owner_info = mock_info(OWNER, &[]);
+        let list_msg = Cw721SellableExecuteMsg::List {
+            listings: Map::from([("Enterprise".to_string(), Uint64::new(30))]),
+            expires: None,
+        };
+        context.execute(owner_info.clone(), list_msg).unwrap();
+
+        let end_time = context.env.block.time.plus_seconds(3600);
+        let create_msg = Cw721SellableExecuteMsg::CreateAuction {
+            token_id: "Enterprise".to_string(),
+            reserve: Uint64::new(100),
+            min_increment: Uint64::new(10),
+            end_time,
+        };
+        context
+            .execute(mock_info(BUYER, &[]), create_msg.clone())
+            .expect_err("expected only the owner to create an auction");
+        context
+            .execute(owner_info.clone(), create_msg.clone())
+            .expect("expected create auction to succeed");
+        let err = context.execute(owner_info.clone(), create_msg).unwrap_err();
+        assert!(matches!(err, ContractError::TokenInAuction));
+
+        let query_res: ListedTokensResponse = context
+            .query(Cw721SellableQueryMsg::ListedTokens {
+                start_after: None,
+                limit: None,
+            })
+            .unwrap();
+        assert_eq!(0, query_res.tokens.len());
+
+        // The token is locked while auctioned
+        let transfer_msg = ExecuteMsg::BaseMsg(cw721_base::ExecuteMsg::TransferNft {
+            recipient: BUYER.to_string(),
+            token_id: "Enterprise".to_string(),
+        });
+        let err = context
+            .execute(owner_info.clone(), transfer_msg)
+            .unwrap_err();
+        assert!(matches!(err, ContractError::TokenInAuction));
+        let burn_msg = ExecuteMsg::BaseMsg(cw721_base::ExecuteMsg::Burn {
+            token_id: "Enterprise".to_string(),
+        });
+        let err = context.execute(owner_info.clone(), burn_msg).unwrap_err();
+        assert!(matches!(err, ContractError::TokenInAuction));
+
+        // Bids must meet the reserve, then beat the highest bid by the increment
+        let bid_msg = Cw721SellableExecuteMsg::Bid {
+            token_id: "Enterprise".to_string(),
+        };
+        let err = context
+            .execute(create_buy_info(BUYER, 99), bid_msg.clone())
+            .unwrap_err();
+        assert!(matches!(err, ContractError::BidTooLow { minimum } if minimum == Uint64::new(100)));
+        let res = context
+            .execute(create_buy_info(BUYER, 100), bid_msg.clone())
+            .unwrap();
+        assert!(res.messages.is_empty());
+
+        let err = context
+            .execute(create_buy_info(NO_MONEY, 109), bid_msg.clone())
+            .unwrap_err();
+        assert!(matches!(err, ContractError::BidTooLow { minimum } if minimum == Uint64::new(110)));
+        let res = context
+            .execute(create_buy_info(NO_MONEY, 200), bid_msg.clone())
+            .unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: BUYER.to_string(),
+                amount: vec![Coin::new(100, DENOM_NAME)],
+            }),
+            "expected the outbid bidder to be refunded"
+        );
+
+        let auction: state::Auction = context
+            .query(Cw721SellableQueryMsg::Auction {
+                token_id: "Enterprise".to_string(),
+            })
+            .unwrap();
+        assert_eq!(
+            auction.highest_bid,
+            Some(state::Bid {
+                bidder: Addr::unchecked(NO_MONEY),
+                amount: Uint64::new(200),
+            })
+        );
+
+        // Settles only after the end time
+        let settle_msg = Cw721SellableExecuteMsg::SettleAuction {
+            token_id: "Enterprise".to_string(),
+        };
+        let err = context
+            .execute(mock_info(BUYER, &[]), settle_msg.clone())
+            .unwrap_err();
+        assert!(matches!(err, ContractError::AuctionNotEnded));
+
+        context.env.block.time = end_time;
+        let err = context
+            .execute(create_buy_info(BUYER, 300), bid_msg)
+            .unwrap_err();
+        assert!(matches!(err, ContractError::AuctionEnded));
+
+        let res = context.execute(mock_info(BUYER, &[]), settle_msg).unwrap();
+        let messages: Vec<_> = res.messages.into_iter().map(|m| m.msg).collect();
+        assert_eq!(
+            messages,
+            vec![
+                CosmosMsg::Bank(BankMsg::Send {
+                    to_address: CREATOR.to_string(),
+                    amount: vec![Coin::new(20, DENOM_NAME)],
+                }),
+                CosmosMsg::Bank(BankMsg::Send {
+                    to_address: OWNER.to_string(),
+                    amount: vec![Coin::new(180, DENOM_NAME)],
+                }),
+            ]
+        );
+
+        let token = context
+            .contract
+            .tokens
+            .load(&context.deps.storage, "Enterprise")
+            .unwrap();
+        assert_eq!(token.owner, Addr::unchecked(NO_MONEY));
+        let auctions: query::AuctionsResponse = context
+            .query(Cw721SellableQueryMsg::Auctions {
+                start_after: None,
+                limit: None,
+            })
+            .unwrap();
+        assert!(auctions.auctions.is_empty());
+    }
+
+    #[test]
+    fn auction_without_bids() {
+        let mut context = Context::default();
+        mint_with_royalty(&mut context, "Voyager");
+
+        let owner_info = mock_info(OWNER, &[]);
+        let end_time = context.env.block.time.plus_seconds(60);
+        let create_msg = Cw721SellableExecuteMsg::CreateAuction {
+            token_id: "Voyager".to_string(),
+            reserve: Uint64::new(100),
+            min_increment: Uint64::new(10),
+            end_time,
+        };
+        context.execute(owner_info.clone(), create_msg).unwrap();
+
+        context.env.block.time = end_time.plus_seconds(1);
+        let res = context
+            .execute(
+                mock_info(OWNER, &[]),
+                Cw721SellableExecuteMsg::SettleAuction {
+                    token_id: "Voyager".to_string(),
+                },
+            )
+            .unwrap();
+        assert!(res.messages.is_empty());
+
+        // The token is free to move again
+        let transfer_msg = ExecuteMsg::BaseMsg(cw721_base::ExecuteMsg::TransferNft {
+            recipient: BUYER.to_string(),
+            token_id: "Voyager".to_string(),
+        });
+        context.execute(owner_info, transfer_msg).unwrap();
+    }
+
+    #[test]
+    fn offers_on_unlisted_tokens() {
+        let mut context = Context::default();
+        mint_with_royalty(&mut context, "Enterprise");
+        mint_with_royalty(&mut context, "Voyager");
+
+        let offer_msg = |token_id: &str, expires| Cw721SellableExecuteMsg::MakeOffer {
+            token_id: token_id.to_string(),
+            expires,
+        };
+
+        // Owners can't make offers on their own tokens, and offers need funds
+        let err = context
+            .execute(create_buy_info(OWNER, 10), offer_msg("Enterprise", None))
+            .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized));
+        let err = context
+            .execute(mock_info(BUYER, &[]), offer_msg("Enterprise", None))
+            .unwrap_err();
+        assert!(matches!(err, ContractError::NoFundsPresent));
+
+        context
+            .execute(create_buy_info(BUYER, 10), offer_msg("Enterprise", None))
+            .unwrap();
+        let expires = Expiration::AtHeight(context.env.block.height + 10);
+        context
+            .execute(
+                create_buy_info(BUYER, 15),
+                offer_msg("Voyager", Some(expires)),
+            )
+            .unwrap();
+        context
+            .execute(create_buy_info(NO_MONEY, 12), offer_msg("Enterprise", None))
+            .unwrap();
+
+        // A new offer replaces and refunds the previous one
+        let res = context
+            .execute(create_buy_info(BUYER, 20), offer_msg("Enterprise", None))
+            .unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: BUYER.to_string(),
+                amount: vec![Coin::new(10, DENOM_NAME)],
+            })
+        );
+
+        let res: query::OffersResponse = context
+            .query(Cw721SellableQueryMsg::OffersByToken {
+                token_id: "Enterprise".to_string(),
+                start_after: None,
+                limit: None,
+            })
+            .unwrap();
+        let bids: Vec<_> = res
+            .offers
+            .iter()
+            .map(|o| (o.bidder.as_str(), o.amount.u64()))
+            .collect();
+        assert_eq!(bids, vec![(BUYER, 20), (NO_MONEY, 12)]);
+        let res: query::OffersResponse = context
+            .query(Cw721SellableQueryMsg::OffersByToken {
+                token_id: "Enterprise".to_string(),
+                start_after: Some(BUYER.to_string()),
+                limit: None,
+            })
+            .unwrap();
+        assert_eq!(res.offers.len(), 1);
+
+        let res: query::OffersResponse = context
+            .query(Cw721SellableQueryMsg::OffersByBidder {
+                bidder: BUYER.to_string(),
+                start_after: None,
+                limit: Some(1),
+            })
+            .unwrap();
+        assert_eq!(res.offers.len(), 1);
+        let first = res.offers[0].token_id.clone();
+        let res: query::OffersResponse = context
+            .query(Cw721SellableQueryMsg::OffersByBidder {
+                bidder: BUYER.to_string(),
+                start_after: Some(first.clone()),
+                limit: None,
+            })
+            .unwrap();
+        assert_eq!(res.offers.len(), 1);
+        assert_ne!(res.offers[0].token_id, first);
+
+        // Only the owner can accept, and only offers that haven't expired
+        let accept_msg = |token_id: &str| Cw721SellableExecuteMsg::AcceptOffer {
+            token_id: token_id.to_string(),
+            bidder: BUYER.to_string(),
+        };
+        let err = context
+            .execute(mock_info(NO_MONEY, &[]), accept_msg("Enterprise"))
+            .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized));
+
+        context.env.block.height += 10;
+        let err = context
+            .execute(mock_info(OWNER, &[]), accept_msg("Voyager"))
+            .unwrap_err();
+        assert!(matches!(err, ContractError::OfferExpired));
+
+        // The token's royalty comes out of the offer
+        let res = context
+            .execute(mock_info(OWNER, &[]), accept_msg("Enterprise"))
+            .unwrap();
+        let messages: Vec<_> = res.messages.into_iter().map(|m| m.msg).collect();
+        assert_eq!(
+            messages,
+            vec![
+                CosmosMsg::Bank(BankMsg::Send {
+                    to_address: CREATOR.to_string(),
+                    amount: vec![Coin::new(2, DENOM_NAME)],
+                }),
+                CosmosMsg::Bank(BankMsg::Send {
+                    to_address: OWNER.to_string(),
+                    amount: vec![Coin::new(18, DENOM_NAME)],
+                }),
+            ]
+        );
+        let token = context
+            .contract
+            .tokens
+            .load(&context.deps.storage, "Enterprise")
+            .unwrap();
+        assert_eq!(token.owner, Addr::unchecked(BUYER));
+
+        // The expired offer can be withdrawn, the accepted one is gone
+        let withdraw_msg = |token_id: &str| Cw721SellableExecuteMsg::WithdrawOffer {
+            token_id: token_id.to_string(),
+        };
+        context
+            .execute(mock_info(BUYER, &[]), withdraw_msg("Enterprise"))
+            .unwrap_err();
+        let res = context
+            .execute(mock_info(BUYER, &[]), withdraw_msg("Voyager"))
+            .unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: BUYER.to_string(),
+                amount: vec![Coin::new(15, DENOM_NAME)],
+            })
+        );
+        let res: query::OffersResponse = context
+            .query(Cw721SellableQueryMsg::OffersByBidder {
+                bidder: BUYER.to_string(),
+                start_after: None,
+                limit: None,
+            })
+            .unwrap();
+        assert!(res.offers.is_empty());
+    }
+
+    #[test]
+    fn collection_offers() {
+        let mut context = Context::default();
+        mint_with_royalty(&mut context, "Enterprise");
+        mint_with_royalty(&mut context, "Voyager");
+
+        let offer_msg = |expires| Cw721SellableExecuteMsg::MakeCollectionOffer { expires };
+        context
+            .execute(create_buy_info(BUYER, 10), offer_msg(None))
+            .unwrap();
+        context
+            .execute(create_buy_info(NO_MONEY, 25), offer_msg(None))
+            .unwrap();
+        let expires = Expiration::AtHeight(context.env.block.height + 10);
+        context
+            .execute(create_buy_info(BUYER, 15), offer_msg(Some(expires)))
+            .unwrap();
+
+        // Best offers come first and can be paginated by offer id
+        let offers_query = |start_after, limit| Cw721SellableQueryMsg::CollectionOffers {
+            start_after,
+            limit,
+            include_expired: None,
+        };
+        let res: query::CollectionOffersResponse = context.query(offers_query(None, None)).unwrap();
+        let ids: Vec<_> = res.offers.iter().map(|o| o.id).collect();
+        assert_eq!(ids, vec![2, 3, 1]);
+        let res: query::CollectionOffersResponse =
+            context.query(offers_query(None, Some(1))).unwrap();
+        assert_eq!(res.offers[0].amount, Uint64::new(25));
+        let res: query::CollectionOffersResponse =
+            context.query(offers_query(Some(2), None)).unwrap();
+        let ids: Vec<_> = res.offers.iter().map(|o| o.id).collect();
+        assert_eq!(ids, vec![3, 1]);
+
+        // Only the owner can fill, and only offers that haven't expired
+        let fill_msg = |offer_id, token_id: &str| Cw721SellableExecuteMsg::FillCollectionOffer {
+            offer_id,
+            token_id: token_id.to_string(),
+        };
+        let err = context
+            .execute(mock_info(BUYER, &[]), fill_msg(2, "Enterprise"))
+            .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized));
+
+        context.env.block.height += 10;
+        let res: query::CollectionOffersResponse = context.query(offers_query(None, None)).unwrap();
+        let ids: Vec<_> = res.offers.iter().map(|o| o.id).collect();
+        assert_eq!(ids, vec![2, 1]);
+        let err = context
+            .execute(mock_info(OWNER, &[]), fill_msg(3, "Enterprise"))
+            .unwrap_err();
+        assert!(matches!(err, ContractError::OfferExpired));
+
+        // The token's royalty comes out of the offer
+        let res = context
+            .execute(mock_info(OWNER, &[]), fill_msg(2, "Voyager"))
+            .unwrap();
+        let messages: Vec<_> = res.messages.into_iter().map(|m| m.msg).collect();
+        assert_eq!(
+            messages,
+            vec![
+                CosmosMsg::Bank(BankMsg::Send {
+                    to_address: CREATOR.to_string(),
+                    amount: vec![Coin::new(2, DENOM_NAME)],
+                }),
+                CosmosMsg::Bank(BankMsg::Send {
+                    to_address: OWNER.to_string(),
+                    amount: vec![Coin::new(23, DENOM_NAME)],
+                }),
+            ]
+        );
+        let token = context
+            .contract
+            .tokens
+            .load(&context.deps.storage, "Voyager")
+            .unwrap();
+        assert_eq!(token.owner, Addr::unchecked(NO_MONEY));
+        context
+            .execute(mock_info(OWNER, &[]), fill_msg(2, "Enterprise"))
+            .unwrap_err();
+
+        // Offers can only be withdrawn by their bidder
+        let withdraw_msg = |offer_id| Cw721SellableExecuteMsg::WithdrawCollectionOffer { offer_id };
+        let err = context
+            .execute(mock_info(NO_MONEY, &[]), withdraw_msg(1))
+            .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized));
+        let res = context
+            .execute(mock_info(BUYER, &[]), withdraw_msg(3))
+            .unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: BUYER.to_string(),
+                amount: vec![Coin::new(15, DENOM_NAME)],
+            })
+        );
+        let res: query::CollectionOffersResponse = context
+            .query(Cw721SellableQueryMsg::CollectionOffers {
+                start_after: None,
+                limit: None,
+                include_expired: Some(true),
+            })
+            .unwrap();
+        let ids: Vec<_> = res.offers.iter().map(|o| o.id).collect();
+        assert_eq!(ids, vec![1]);
+    }
+
+    #[test]
+    fn sales_respect_transfer_restriction() {
+        let mut context = Context::default();
+        for token_id in ["Enterprise", "Voyager", "Defiant", "Discovery"] {
+            context
+                .mint(CREATOR, token_id, OWNER, Metadata::default())
+                .unwrap();
+        }
+        let creator_info = mock_info(CREATOR, &[]);
+        context
+            .execute(
+                creator_info.clone(),
+                ExecuteMsg::BaseMsg(cw721_base::ExecuteMsg::SetTransferRestriction {
+                    restriction: Some(cw721_base::state::TransferRestriction::Denylist),
+                }),
+            )
+            .unwrap();
+        context
+            .execute(
+                creator_info.clone(),
+                ExecuteMsg::BaseMsg(cw721_base::ExecuteMsg::UpdateRestrictedAddresses {
+                    add: vec![BUYER.to_string()],
+                    remove: vec![],
+                }),
+            )
+            .unwrap();
+        let is_denied = |err: ContractError| {
+            matches!(
+                err,
+                ContractError::BaseError(cw721_base::ContractError::RecipientNotAllowed {
+                    recipient,
+                }) if recipient == BUYER
+            )
+        };
+
+        // Buy
+        let owner_info = mock_info(OWNER, &[]);
+        let list_msg = Cw721SellableExecuteMsg::List {
+            listings: Map::from([("Enterprise".to_string(), Uint64::new(30))]),
+            expires: None,
+        };
+        context.execute(owner_info.clone(), list_msg).unwrap();
+        let err = context
+            .execute(create_buy_info(BUYER, 30), Cw721SellableExecuteMsg::Buy {})
+            .unwrap_err();
+        assert!(is_denied(err));
+
+        // AcceptOffer
+        let offer_msg = Cw721SellableExecuteMsg::MakeOffer {
+            token_id: "Voyager".to_string(),
+            expires: None,
+        };
+        context
+            .execute(create_buy_info(BUYER, 20), offer_msg)
+            .unwrap();
+        let accept_msg = Cw721SellableExecuteMsg::AcceptOffer {
+            token_id: "Voyager".to_string(),
+            bidder: BUYER.to_string(),
+        };
+        let err = context.execute(owner_info.clone(), accept_msg).unwrap_err();
+        assert!(is_denied(err));
+
+        // FillCollectionOffer
+        let offer_msg = Cw721SellableExecuteMsg::MakeCollectionOffer { expires: None };
+        context
+            .execute(create_buy_info(BUYER, 20), offer_msg)
+            .unwrap();
+        let fill_msg = Cw721SellableExecuteMsg::FillCollectionOffer {
+            offer_id: 1,
+            token_id: "Defiant".to_string(),
+        };
+        let err = context.execute(owner_info.clone(), fill_msg).unwrap_err();
+        assert!(is_denied(err));
+
+        // Bid, restricted bidders can't bid in the first place
+        let end_time = context.env.block.time.plus_seconds(3600);
+        let create_msg = Cw721SellableExecuteMsg::CreateAuction {
+            token_id: "Discovery".to_string(),
+            reserve: Uint64::new(100),
+            min_increment: Uint64::new(10),
+            end_time,
+        };
+        context.execute(owner_info.clone(), create_msg).unwrap();
+        let bid_msg = Cw721SellableExecuteMsg::Bid {
+            token_id: "Discovery".to_string(),
+        };
+        let err = context
+            .execute(create_buy_info(BUYER, 100), bid_msg.clone())
+            .unwrap_err();
+        assert!(is_denied(err));
+
+        // SettleAuction, a winner restricted after bidding is refunded
+        context
+            .execute(create_buy_info(NO_MONEY, 100), bid_msg)
+            .unwrap();
+        context
+            .execute(
+                creator_info,
+                ExecuteMsg::BaseMsg(cw721_base::ExecuteMsg::UpdateRestrictedAddresses {
+                    add: vec![NO_MONEY.to_string()],
+                    remove: vec![],
+                }),
+            )
+            .unwrap();
+        context.env.block.time = end_time;
+        let settle_msg = Cw721SellableExecuteMsg::SettleAuction {
+            token_id: "Discovery".to_string(),
+        };
+        let res = context.execute(mock_info(BUYER, &[]), settle_msg).unwrap();
+        let messages: Vec<_> = res.messages.into_iter().map(|m| m.msg).collect();
+        assert_eq!(
+            messages,
+            vec![CosmosMsg::Bank(BankMsg::Send {
+                to_address: NO_MONEY.to_string(),
+                amount: vec![Coin::new(100, DENOM_NAME)],
+            })]
+        );
+        let auctions: query::AuctionsResponse = context
+            .query(Cw721SellableQueryMsg::Auctions {
+                start_after: None,
+                limit: None,
+            })
+            .unwrap();
+        assert!(auctions.auctions.is_empty());
+
+        // None of the tokens changed hands, and the auctioned one is released
+        for token_id in ["Enterprise", "Voyager", "Defiant", "Discovery"] {
+            let token = context
+                .contract
+                .tokens
+                .load(&context.deps.storage, token_id)
+                .unwrap();
+            assert_eq!(token.owner, Addr::unchecked(OWNER));
+        }
+        let list_msg = Cw721SellableExecuteMsg::List {
+            listings: Map::from([("Discovery".to_string(), Uint64::new(30))]),
+            expires: None,
+        };
+        context.execute(owner_info, list_msg).unwrap();
+    }
+
+    #[test]
+    fn listing_expiration() {
+        let mut context = Context::default();
+        context
+            .mint(CREATOR, "Enterprise", OWNER, Metadata::default())
+            .unwrap();
+        context
+            .mint(CREATOR, "Voyager", OWNER, Metadata::default())
+            .unwrap();
+
+        let list_msg = |token_id: &str, price: u64, expires| Cw721SellableExecuteMsg::List {
+            listings: Map::from([(token_id.to_string(), Uint64::new(price))]),
+            expires,
+        };
+        let err = context
+            .execute(
+                mock_info(OWNER, &[]),
+                list_msg("Enterprise", 10, Some(Expiration::AtHeight(1))),
+            )
+            .unwrap_err();
+        assert!(matches!(err, ContractError::InvalidListExpiration));
+
+        let expires = Expiration::AtHeight(context.env.block.height + 10);
+        context
+            .execute(
+                mock_info(OWNER, &[]),
+                list_msg("Enterprise", 10, Some(expires)),
+            )
+            .unwrap();
+        context
+            .execute(mock_info(OWNER, &[]), list_msg("Voyager", 20, None))
+            .unwrap();
+
+        let query_msg = Cw721SellableQueryMsg::ListedTokens {
+            start_after: None,
+            limit: None,
+        };
+        let res: ListedTokensResponse = context.query(query_msg.clone()).unwrap();
+        assert_eq!(res.tokens.len(), 2);
+
+        // Once expired, the cheaper listing is skipped by both queries and buys
+        context.env.block.height += 10;
+        let res: ListedTokensResponse = context.query(query_msg).unwrap();
+        let ids: Vec<_> = res.tokens.iter().map(|(id, _)| id.as_str()).collect();
+        assert_eq!(ids, vec!["Voyager"]);
+
+        context
+            .execute(create_buy_info(BUYER, 20), Cw721SellableExecuteMsg::Buy {})
+            .unwrap();
+        let token = context
+            .contract
+            .tokens
+            .load(&context.deps.storage, "Voyager")
+            .unwrap();
+        assert_eq!(token.owner, Addr::unchecked(BUYER));
+        let err = context
+            .execute(create_buy_info(BUYER, 20), Cw721SellableExecuteMsg::Buy {})
+            .unwrap_err();
+        assert!(matches!(err, ContractError::NoListedTokensError));
+    }
+
+    #[test]
+    fn resale_price_rules() {
+        let mut context = Context::default();
+        context
+            .mint(CREATOR, "Enterprise", OWNER, Metadata::default())
+            .unwrap();
+        context
+            .contract
+            .contract_metadata
+            .save(
+                &mut context.deps.storage,
+                &ContractMetadata {
+                    initial_price: Uint64::new(20),
+                    ..ContractMetadata::default()
+                },
+            )
+            .unwrap();
+
+        let rules_msg = Cw721SellableExecuteMsg::UpdateResaleRules {
+            max_resale_price: Some(Uint64::new(50)),
+            min_resale_percentage: Some(50),
+        };
+        let err = context
+            .execute(mock_info(OWNER, &[]), rules_msg.clone())
+            .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized));
+        context.execute(mock_info(CREATOR, &[]), rules_msg).unwrap();
+
+        let list_msg = |price: u64| Cw721SellableExecuteMsg::List {
+            listings: Map::from([("Enterprise".to_string(), Uint64::new(price))]),
+            expires: None,
+        };
+        let err = context
+            .execute(mock_info(OWNER, &[]), list_msg(51))
+            .unwrap_err();
+        assert!(matches!(err, ContractError::ListPriceTooHigh { max } if max.u64() == 50));
+        let err = context
+            .execute(mock_info(OWNER, &[]), list_msg(9))
+            .unwrap_err();
+        assert!(matches!(err, ContractError::ListPriceTooLow { min } if min.u64() == 10));
+        context
+            .execute(mock_info(OWNER, &[]), list_msg(50))
+            .unwrap();
+        // Delisting is always allowed
+        context.execute(mock_info(OWNER, &[]), list_msg(0)).unwrap();
+
+        // The minter's own tickets are not resales
+        let transfer_msg = ExecuteMsg::BaseMsg(cw721_base::ExecuteMsg::TransferNft {
+            recipient: CREATOR.to_string(),
+            token_id: "Enterprise".to_string(),
+        });
+        context
+            .execute(mock_info(OWNER, &[]), transfer_msg)
+            .unwrap();
+        context
+            .execute(mock_info(CREATOR, &[]), list_msg(100))
+            .unwrap();
+    }
+
+    #[test]
+    fn basis_point_royalties() {
+        let mut context = Context::default();
+        let mint_msg = |token_id: &str, percentage, basis_points| {
+            ExecuteMsg::BaseMsg(cw721_base::ExecuteMsg::Mint(cw721_base::MintMsg {
+                token_id: token_id.to_string(),
+                owner: OWNER.to_string(),
+                token_uri: None,
+                extension: Some(Metadata {
+                    royalty_percentage: percentage,
+                    royalty_basis_points: basis_points,
+                    royalty_payment_address: Some(CREATOR.to_string()),
+                    ..Metadata::default()
+                }),
+            }))
+        };
+
+        // Royalties above the whole sale price are rejected
+        let err = context
+            .execute(
+                mock_info(CREATOR, &[]),
+                mint_msg("Enterprise", Some(101), None),
+            )
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::RoyaltyError(
+                cw2981_royalties::ContractError::InvalidRoyaltyPercentage { percentage: 101 }
+            )
+        ));
+        let err = context
+            .execute(
+                mock_info(CREATOR, &[]),
+                mint_msg("Enterprise", None, Some(10_001)),
+            )
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::RoyaltyError(
+                cw2981_royalties::ContractError::InvalidRoyaltyBasisPoints {
+                    basis_points: 10_001
+                }
+            )
+        ));
+
+        // Basis points take precedence over the percentage
+        context
+            .execute(
+                mock_info(CREATOR, &[]),
+                mint_msg("Enterprise", Some(10), Some(250)),
+            )
+            .unwrap();
+        let end_time = context.env.block.time.plus_seconds(3600);
+        let create_msg = Cw721SellableExecuteMsg::CreateAuction {
+            token_id: "Enterprise".to_string(),
+            reserve: Uint64::new(1000),
+            min_increment: Uint64::new(10),
+            end_time,
+        };
+        context.execute(mock_info(OWNER, &[]), create_msg).unwrap();
+        let bid_msg = Cw721SellableExecuteMsg::Bid {
+            token_id: "Enterprise".to_string(),
+        };
+        context
+            .execute(create_buy_info(BUYER, 1000), bid_msg)
+            .unwrap();
+        context.env.block.time = end_time;
+        let settle_msg = Cw721SellableExecuteMsg::SettleAuction {
+            token_id: "Enterprise".to_string(),
+        };
+        let res = context.execute(mock_info(BUYER, &[]), settle_msg).unwrap();
+        let payouts: Vec<_> = res.messages.iter().map(|m| m.msg.clone()).collect();
+        assert_eq!(
+            payouts,
+            vec![
+                CosmosMsg::Bank(BankMsg::Send {
+                    to_address: CREATOR.to_string(),
+                    amount: vec![Coin::new(25, DENOM_NAME)],
+                }),
+                CosmosMsg::Bank(BankMsg::Send {
+                    to_address: OWNER.to_string(),
+                    amount: vec![Coin::new(975, DENOM_NAME)],
+                }),
+            ]
+        );
+
+        // The contract wide royalty is checked at instantiation
+        let mut deps = mock_dependencies();
+        let instantiate_msg = InstantiateMsg {
+            name: "Burnt Ticketing".to_string(),
+            symbol: "BRNT".to_string(),
+            minter: CREATOR.to_string(),
+            contract_metadata: ContractMetadata {
+                royalty_basis_points: Some(20_000),
+                num_of_tickets: Uint64::new(1),
+                ..ContractMetadata::default()
+            },
+        };
+        let err = instantiate(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(CREATOR, &[]),
+            instantiate_msg,
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::RoyaltyError(
+                cw2981_royalties::ContractError::InvalidRoyaltyBasisPoints {
+                    basis_points: 20_000
+                }
+            )
+        ));
+    }
+
+    #[test]
+    fn royalty_schedule_after_resales() {
+        let mut context = Context::default();
+        let mint_msg = cw721_base::MintMsg {
+            token_id: "Enterprise".to_string(),
+            owner: OWNER.to_string(),
+            token_uri: None,
+            extension: Some(Metadata {
+                royalty_percentage: Some(10),
+                royalty_payment_address: Some(CREATOR.to_string()),
+                royalty_schedule: Some(vec![RoyaltyStep {
+                    threshold: cw2981_royalties::RoyaltyThreshold::Resales(1),
+                    basis_points: 100,
+                }]),
+                ..Metadata::default()
+            }),
+        };
+        let exec_msg = ExecuteMsg::BaseMsg(cw721_base::ExecuteMsg::Mint(mint_msg));
+        context.execute(mock_info(CREATOR, &[]), exec_msg).unwrap();
+
+        // Purchases pay the royalty and count as resales
+        let list_msg = Cw721SellableExecuteMsg::List {
+            listings: Map::from([("Enterprise".to_string(), Uint64::new(100))]),
+            expires: None,
+        };
+        context.execute(mock_info(OWNER, &[]), list_msg).unwrap();
+        let res = context
+            .execute(create_buy_info(BUYER, 100), Cw721SellableExecuteMsg::Buy {})
+            .unwrap();
+        let payouts: Vec<_> = res.messages.iter().map(|m| m.msg.clone()).collect();
+        assert_eq!(
+            payouts,
+            vec![
+                CosmosMsg::Bank(BankMsg::Send {
+                    to_address: CREATOR.to_string(),
+                    amount: vec![Coin::new(10, DENOM_NAME)],
+                }),
+                CosmosMsg::Bank(BankMsg::Send {
+                    to_address: OWNER.to_string(),
+                    amount: vec![Coin::new(90, DENOM_NAME)],
+                }),
+            ]
+        );
+        let resales = cw2981_royalties::state::RESALES
+            .load(&context.deps.storage, "Enterprise")
+            .unwrap();
+        assert_eq!(resales, 1);
+
+        // Once resold, the royalty drops from 10% to 1%
+        let end_time = context.env.block.time.plus_seconds(3600);
+        let create_msg = Cw721SellableExecuteMsg::CreateAuction {
+            token_id: "Enterprise".to_string(),
+            reserve: Uint64::new(1000),
+            min_increment: Uint64::new(10),
+            end_time,
+        };
+        context.execute(mock_info(BUYER, &[]), create_msg).unwrap();
+        let bid_msg = Cw721SellableExecuteMsg::Bid {
+            token_id: "Enterprise".to_string(),
+        };
+        context
+            .execute(create_buy_info(NO_MONEY, 1000), bid_msg)
+            .unwrap();
+        context.env.block.time = end_time;
+        let settle_msg = Cw721SellableExecuteMsg::SettleAuction {
+            token_id: "Enterprise".to_string(),
+        };
+        let res = context.execute(mock_info(BUYER, &[]), settle_msg).unwrap();
+        let payouts: Vec<_> = res.messages.iter().map(|m| m.msg.clone()).collect();
+        assert_eq!(
+            payouts,
+            vec![
+                CosmosMsg::Bank(BankMsg::Send {
+                    to_address: CREATOR.to_string(),
+                    amount: vec![Coin::new(10, DENOM_NAME)],
+                }),
+                CosmosMsg::Bank(BankMsg::Send {
+                    to_address: BUYER.to_string(),
+                    amount: vec![Coin::new(990, DENOM_NAME)],
+                }),
+            ]
+        );
+        let resales = cw2981_royalties::state::RESALES
+            .load(&context.deps.storage, "Enterprise")
+            .unwrap();
+        assert_eq!(resales, 2);
+
+        // A later step can't be reached before an earlier one of the same kind
+        let mint_msg = cw721_base::MintMsg {
+            token_id: "Voyager".to_string(),
+            owner: OWNER.to_string(),
+            token_uri: None,
+            extension: Some(Metadata {
+                royalty_schedule: Some(vec![
+                    RoyaltyStep {
+                        threshold: cw2981_royalties::RoyaltyThreshold::Resales(2),
+                        basis_points: 100,
+                    },
+                    RoyaltyStep {
+                        threshold: cw2981_royalties::RoyaltyThreshold::Resales(1),
+                        basis_points: 500,
+                    },
+                ]),
+                ..Metadata::default()
+            }),
+        };
+        let exec_msg = ExecuteMsg::BaseMsg(cw721_base::ExecuteMsg::Mint(mint_msg));
+        let err = context
+            .execute(mock_info(CREATOR, &[]), exec_msg)
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::RoyaltyError(
+                cw2981_royalties::ContractError::UnorderedRoyaltySchedule {}
+            )
+        ));
+    }
+
+    #[test]
+    fn sale_events_and_history() {
+        let mut context = Context::default();
+        context
+            .mint(CREATOR, "Enterprise", OWNER, Metadata::default())
+            .unwrap();
+
+        let list_msg = |price: u64| Cw721SellableExecuteMsg::List {
+            listings: Map::from([("Enterprise".to_string(), Uint64::new(price))]),
+            expires: None,
+        };
+        let res = context
+            .execute(mock_info(OWNER, &[]), list_msg(10))
+            .unwrap();
+        assert_eq!(
+            res.events,
+            vec![Event::new("list")
+                .add_attribute("token_id", "Enterprise")
+                .add_attribute("seller", OWNER)
+                .add_attribute("price", "10")]
+        );
+        let res = context.execute(mock_info(OWNER, &[]), list_msg(0)).unwrap();
+        assert_eq!(
+            res.events,
+            vec![Event::new("delist")
+                .add_attribute("token_id", "Enterprise")
+                .add_attribute("seller", OWNER)]
+        );
+
+        // Sell the token back and forth until the history is full
+        let mut seller = OWNER;
+        let mut buyer = BUYER;
+        for _ in 0..=state::MAX_SALE_HISTORY {
+            context
+                .execute(mock_info(seller, &[]), list_msg(10))
+                .unwrap();
+            let res = context
+                .execute(create_buy_info(buyer, 10), Cw721SellableExecuteMsg::Buy {})
+                .unwrap();
+            assert_eq!(
+                res.events,
+                vec![Event::new("sale")
+                    .add_attribute("token_id", "Enterprise")
+                    .add_attribute("seller", seller)
+                    .add_attribute("buyer", buyer)
+                    .add_attribute("price", "10")]
+            );
+            std::mem::swap(&mut seller, &mut buyer);
+        }
+
+        let history_msg = |start_after, limit| Cw721SellableQueryMsg::SaleHistory {
+            token_id: "Enterprise".to_string(),
+            start_after,
+            limit,
+        };
+        let res: query::SaleHistoryResponse = context.query(history_msg(None, None)).unwrap();
+        assert_eq!(res.sales.len() as u64, state::MAX_SALE_HISTORY);
+        // the first sale was dropped
+        let (first, sale) = &res.sales[0];
+        assert_eq!(*first, 2);
+        assert_eq!(sale.seller, Addr::unchecked(BUYER));
+        assert_eq!(sale.buyer, Addr::unchecked(OWNER));
+        assert_eq!(sale.time, context.env.block.time);
+
+        let res: query::SaleHistoryResponse = context
+            .query(history_msg(Some(state::MAX_SALE_HISTORY), Some(10)))
+            .unwrap();
+        let numbers: Vec<_> = res.sales.iter().map(|(n, _)| *n).collect();
+        assert_eq!(numbers, vec![state::MAX_SALE_HISTORY + 1]);
+
+        let res = context
+            .execute(
+                mock_info(CREATOR, &[]),
+                Cw721SellableExecuteMsg::RedeemTicket {
+                    address: seller.to_string(),
+                    ticket_id: "Enterprise".to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            res.events,
+            vec![Event::new("redeem")
+                .add_attribute("token_id", "Enterprise")
+                .add_attribute("owner", seller)]
+        );
+    }
+
+    #[test]
+    fn paused_market() {
+        let mut context = Context::default();
+        context
+            .mint(CREATOR, "Enterprise", OWNER, Metadata::default())
+            .unwrap();
+        context
+            .mint(CREATOR, "Voyager", OWNER, Metadata::default())
+            .unwrap();
+        let list_msg = Cw721SellableExecuteMsg::List {
+            listings: Map::from([("Enterprise".to_string(), Uint64::new(10))]),
+            expires: None,
+        };
+        context
+            .execute(mock_info(OWNER, &[]), list_msg.clone())
+            .unwrap();
+
+        context
+            .execute(
+                mock_info(CREATOR, &[]),
+                ExecuteMsg::BaseMsg(cw721_base::ExecuteMsg::Pause {}),
+            )
+            .unwrap();
+
+        let blocked = [
+            (mock_info(OWNER, &[]), list_msg),
+            (create_buy_info(BUYER, 10), Cw721SellableExecuteMsg::Buy {}),
+            (
+                mock_info(CREATOR, &[]),
+                Cw721SellableExecuteMsg::RedeemTicket {
+                    address: OWNER.to_string(),
+                    ticket_id: "Voyager".to_string(),
+                },
+            ),
+        ];
+        for (info, msg) in blocked {
+            let err = context.execute(info, msg).unwrap_err();
+            assert!(
+                matches!(
+                    err,
+                    ContractError::BaseError(cw721_base::ContractError::Paused {})
+                ),
+                "unexpected error: {:?}",
+                err
+            );
+        }
+
+        // the listing can still be seen
+        let res: ListedTokensResponse = context
+            .query(Cw721SellableQueryMsg::ListedTokens {
+                start_after: None,
+                limit: None,
+            })
+            .unwrap();
+        assert_eq!(res.tokens.len(), 1);
+
+        context
+            .execute(
+                mock_info(CREATOR, &[]),
+                ExecuteMsg::BaseMsg(cw721_base::ExecuteMsg::Unpause {}),
+            )
+            .unwrap();
+        context
+            .execute(create_buy_info(BUYER, 10), Cw721SellableExecuteMsg::Buy {})
+            .unwrap();
+    }
+
+    #[test]
+    fn update_contract_metadata() {
+        let mut context = Context::default();
+        let update_msg = |royalty_basis_points| {
+            ExecuteMsg::BaseMsg(cw721_base::ExecuteMsg::UpdateContractMetadata(
+                ContractMetadata {
+                    description: "Opening night".to_string(),
+                    initial_price: Uint64::new(20),
+                    royalty_basis_points,
+                    ..ContractMetadata::default()
+                },
+            ))
+        };
+
+        let err = context
+            .execute(mock_info(OWNER, &[]), update_msg(Some(500)))
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::BaseError(cw721_base::ContractError::Unauthorized {})
+        ));
+        let err = context
+            .execute(mock_info(CREATOR, &[]), update_msg(Some(10_001)))
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::RoyaltyError(
+                cw2981_royalties::ContractError::InvalidRoyaltyBasisPoints {
+                    basis_points: 10_001
+                }
+            )
+        ));
+
+        context
+            .execute(mock_info(CREATOR, &[]), update_msg(Some(500)))
+            .unwrap();
+        let res: cw721::ContractMetadataResponse<ContractMetadata> =
+            context.query(QueryMsg::ContractMetadata {}).unwrap();
+        let metadata = res.metadata.unwrap();
+        assert_eq!(metadata.description, "Opening night");
+        assert_eq!(metadata.royalty_basis_points, Some(500));
+    }
+
+    #[test]
+    fn base_msg_conversion() {
+        let burn_msg = cw721_base::ExecuteMsg::Burn {
+            token_id: "Enterprise".to_string(),
+        };
+        let msg = cw721_base::ExecuteMsg::try_from(ExecuteMsg::BaseMsg(burn_msg.clone())).unwrap();
+        assert_eq!(msg, burn_msg);
+
+        // the sellable messages have no base counterpart
+        let err = cw721_base::ExecuteMsg::try_from(ExecuteMsg::RedeemTicket {
+            address: OWNER.to_string(),
+            ticket_id: "Enterprise".to_string(),
+        })
+        .unwrap_err();
+        assert!(matches!(err, ContractError::NotBaseMsg));
     }
 }
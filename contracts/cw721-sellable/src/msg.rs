@@ -1,9 +1,10 @@
-use cosmwasm_std::Uint64;
+use cosmwasm_std::{Timestamp, Uint64};
+use cw721::Expiration;
 use schemars::{JsonSchema, Map};
 use serde::{Deserialize, Serialize};
 
+use crate::error::ContractError;
 use crate::{ContractMetadata, Extension};
-use cw2981_royalties::msg::Cw2981QueryMsg;
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 pub struct InstantiateMsg {
     /// Name of the NFT contract
@@ -22,13 +23,15 @@ pub struct InstantiateMsg {
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 #[serde(rename_all = "snake_case")]
 pub enum Cw721SellableExecuteMsg<T> {
-    BaseMsg(cw721_base::ExecuteMsg<T>),
+    BaseMsg(cw721_base::ExecuteMsg<T, ContractMetadata>),
 
     /// Sellable specific functions
 
-    /// Lists the NFT at the given price
+    /// Lists the NFTs at the given prices, a price of zero delists. Listings
+    /// can no longer be bought once `expires` has passed
     List {
         listings: Map<String, Uint64>,
+        expires: Option<Expiration>,
     },
 
     /// Purchases the cheapest listed NFT. The value passed along with the
@@ -40,53 +43,152 @@ pub enum Cw721SellableExecuteMsg<T> {
         address: String,
         ticket_id: String,
     },
+
+    /// Auctions the NFT to the highest bidder. The token can't be moved,
+    /// listed or redeemed until the auction is settled
+    CreateAuction {
+        token_id: String,
+        /// Lowest acceptable first bid
+        reserve: Uint64,
+        /// Every following bid must beat the highest bid by at least this much
+        min_increment: Uint64,
+        end_time: Timestamp,
+    },
+
+    /// Bids the funds sent with the transaction on a running auction.
+    /// The funds are held by the contract and refunded when outbid
+    Bid {
+        token_id: String,
+    },
+
+    /// Ends an auction after its end time, transferring the token to the
+    /// highest bidder and paying the seller minus royalties
+    SettleAuction {
+        token_id: String,
+    },
+
+    /// Offers the funds sent with the transaction for a token, whether it is
+    /// listed or not. Replaces and refunds any previous offer by the sender
+    MakeOffer {
+        token_id: String,
+        expires: Option<Expiration>,
+    },
+
+    /// Sells the token to the given bidder at their offered price
+    AcceptOffer {
+        token_id: String,
+        bidder: String,
+    },
+
+    /// Cancels the sender's offer on a token and refunds it
+    WithdrawOffer {
+        token_id: String,
+    },
+
+    /// Offers the funds sent with the transaction for any one token of the
+    /// collection
+    MakeCollectionOffer {
+        expires: Option<Expiration>,
+    },
+
+    /// Sells the token to the bidder of a collection offer at the offered price
+    FillCollectionOffer {
+        offer_id: u64,
+        token_id: String,
+    },
+
+    /// Cancels one of the sender's collection offers and refunds it
+    WithdrawCollectionOffer {
+        offer_id: u64,
+    },
+
+    /// Sets the price range tickets can be resold in. Only the minter can
+    /// call this, and its own listings are not restricted
+    UpdateResaleRules {
+        max_resale_price: Option<Uint64>,
+        /// Percentage of the contract's `initial_price`
+        min_resale_percentage: Option<u64>,
+    },
 }
 
-type BaseExecuteMsg = cw721_base::ExecuteMsg<Extension>;
+type BaseExecuteMsg = cw721_base::ExecuteMsg<Extension, ContractMetadata>;
 
-impl From<Cw721SellableExecuteMsg<Extension>> for BaseExecuteMsg {
-    fn from(msg: Cw721SellableExecuteMsg<Extension>) -> BaseExecuteMsg {
-        use Cw721SellableExecuteMsg::BaseMsg;
+impl TryFrom<Cw721SellableExecuteMsg<Extension>> for BaseExecuteMsg {
+    type Error = ContractError;
 
+    fn try_from(msg: Cw721SellableExecuteMsg<Extension>) -> Result<Self, Self::Error> {
         match msg {
-            BaseMsg(msg) => msg,
-            _ => panic!("cannot covert {:?} to Cw2981QueryMsg", msg),
+            Cw721SellableExecuteMsg::BaseMsg(msg) => Ok(msg),
+            _ => Err(ContractError::NotBaseMsg),
         }
     }
 }
 
-type BaseInstantiateMsg = cw2981_royalties::InstantiateMsg;
+type BaseInstantiateMsg = cw721_base::InstantiateMsg<ContractMetadata>;
 
 impl From<InstantiateMsg> for BaseInstantiateMsg {
     fn from(msg: InstantiateMsg) -> BaseInstantiateMsg {
-        // remove contract wide metadata
         BaseInstantiateMsg {
             name: msg.name,
             symbol: msg.symbol,
             minter: msg.minter,
+            soulbound: false,
+            contract_metadata: Some(msg.contract_metadata),
         }
     }
 }
 
+/// The sellable queries, sent as `QueryMsg::Extension { msg }` alongside the cw721 ones
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 #[serde(rename_all = "snake_case")]
 pub enum Cw721SellableQueryMsg {
-    /// Returns all currently listed tokens
+    /// Returns all currently listed tokens whose listing has not expired
     ListedTokens {
         start_after: Option<String>,
         limit: Option<u32>,
     },
 
-    Cw2981Query(Cw2981QueryMsg),
-}
+    /// Returns the running auction of a token
+    Auction { token_id: String },
 
-impl From<Cw721SellableQueryMsg> for Cw2981QueryMsg {
-    fn from(msg: Cw721SellableQueryMsg) -> Cw2981QueryMsg {
-        use Cw721SellableQueryMsg::Cw2981Query;
+    /// Returns all running auctions
+    Auctions {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
 
-        match msg {
-            Cw2981Query(msg) => msg,
-            _ => panic!("cannot covert {:?} to Cw2981QueryMsg", msg),
-        }
+    /// Returns the offers made on a token, ordered by bidder
+    OffersByToken {
+        token_id: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    /// Returns the offers made by a bidder, paginated by token id
+    OffersByBidder {
+        bidder: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    /// Returns the collection offers, highest amount first. Expired offers
+    /// are left out unless `include_expired` is set
+    CollectionOffers {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+        include_expired: Option<bool>,
+    },
+
+    /// Returns the most recent sales of a token by sale number, oldest first
+    SaleHistory {
+        token_id: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+}
+
+impl From<Cw721SellableQueryMsg> for cw721_base::QueryMsg<Cw721SellableQueryMsg> {
+    fn from(msg: Cw721SellableQueryMsg) -> Self {
+        cw721_base::QueryMsg::Extension { msg }
     }
 }
@@ -1,7 +1,11 @@
-use crate::{Cw721SellableContract, Extension, Metadata};
-use cosmwasm_std::{Deps, Order, StdResult};
+use crate::state::{
+    collection_offers, offers, Auction, CollectionOffer, Offer, Sale, AUCTIONS, SALE_HISTORY,
+};
+use crate::{Cw721SellableContract, Extension};
+use cosmwasm_std::{Deps, Env, Order, StdResult};
 use cw721_base::state::TokenInfo;
 use cw_storage_plus::Bound;
+use cw_utils::maybe_addr;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
@@ -10,6 +14,7 @@ const MAX_LIMIT: u32 = 10000;
 
 pub fn listed_tokens(
     deps: Deps,
+    env: Env,
     start_after: Option<String>,
     limit: Option<u32>,
 ) -> StdResult<ListedTokensResponse> {
@@ -21,21 +26,12 @@ pub fn listed_tokens(
     let token_vec: Vec<(String, TokenInfo<Extension>)> = contract
         .tokens
         .range(deps.storage, start, None, Order::Ascending)
-        .flat_map(|result| match result {
-            Ok(
-                pair @ (
-                    _,
-                    TokenInfo {
-                        extension:
-                            Some(Metadata {
-                                list_price: Some(_),
-                                ..
-                            }),
-                        ..
-                    },
-                ),
-            ) => Some(pair),
-            _ => None,
+        .flatten()
+        .filter(|(_, token)| match &token.extension {
+            Some(metadata) => {
+                metadata.list_price.is_some() && !metadata.listing_expired(&env.block)
+            }
+            None => false,
         })
         .take(limit)
         .collect();
@@ -50,3 +46,137 @@ pub struct ListedTokensResponse {
     /// to achieve pagination.
     pub tokens: Vec<(String, TokenInfo<Extension>)>,
 }
+
+pub fn auction(deps: Deps, token_id: String) -> StdResult<Auction> {
+    AUCTIONS.load(deps.storage, &token_id)
+}
+
+pub fn auctions(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<AuctionsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.as_deref().map(Bound::exclusive);
+
+    let auctions = AUCTIONS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(AuctionsResponse { auctions })
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct AuctionsResponse {
+    /// Running auctions by token id in lexicographical ordering
+    pub auctions: Vec<(String, Auction)>,
+}
+
+pub fn offers_by_token(
+    deps: Deps,
+    token_id: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<OffersResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_addr = maybe_addr(deps.api, start_after)?;
+    let start = start_addr.as_ref().map(Bound::exclusive);
+
+    let offers = offers()
+        .prefix(&token_id)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, offer)| offer))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(OffersResponse { offers })
+}
+
+pub fn offers_by_bidder(
+    deps: Deps,
+    bidder: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<OffersResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let bidder = deps.api.addr_validate(&bidder)?;
+    let start = start_after.map(|token_id| Bound::exclusive((token_id, bidder.clone())));
+
+    let offers = offers()
+        .idx
+        .bidder
+        .prefix(bidder)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, offer)| offer))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(OffersResponse { offers })
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct OffersResponse {
+    pub offers: Vec<Offer>,
+}
+
+pub fn collection_offers_by_price(
+    deps: Deps,
+    env: Env,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+    include_expired: bool,
+) -> StdResult<CollectionOffersResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    // descending by (amount, id), so the next page ends below the last offer seen
+    let end = match start_after {
+        Some(id) => {
+            let offer = collection_offers().load(deps.storage, id)?;
+            Some(Bound::exclusive((offer.amount.u64(), id)))
+        }
+        None => None,
+    };
+
+    let offers = collection_offers()
+        .idx
+        .price
+        .range(deps.storage, None, end, Order::Descending)
+        .filter(|item| match item {
+            Ok((_, offer)) => include_expired || !offer.expires.is_expired(&env.block),
+            Err(_) => true,
+        })
+        .take(limit)
+        .map(|item| item.map(|(_, offer)| offer))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(CollectionOffersResponse { offers })
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct CollectionOffersResponse {
+    pub offers: Vec<CollectionOffer>,
+}
+
+pub fn sale_history(
+    deps: Deps,
+    token_id: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<SaleHistoryResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let sales = SALE_HISTORY
+        .prefix(&token_id)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(SaleHistoryResponse { sales })
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct SaleHistoryResponse {
+    /// Sales by sale number, use the last one as `start_after` to page forward
+    pub sales: Vec<(u64, Sale)>,
+}
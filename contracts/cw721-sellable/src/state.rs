@@ -0,0 +1,110 @@
+use cosmwasm_std::{Addr, Timestamp, Uint64};
+use cw721::Expiration;
+use cw_storage_plus::{Index, IndexList, IndexedMap, Item, Map, MultiIndex};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct Auction {
+    /// Owner of the token when the auction was created, receives the proceeds
+    pub seller: Addr,
+    /// Lowest acceptable first bid
+    pub reserve: Uint64,
+    /// Every bid after the first must beat the highest bid by at least this much
+    pub min_increment: Uint64,
+    /// No bids are accepted from this time on and the auction can be settled
+    pub end_time: Timestamp,
+    /// The current highest bid, its funds are held by the contract
+    pub highest_bid: Option<Bid>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct Bid {
+    pub bidder: Addr,
+    pub amount: Uint64,
+}
+
+/// Running auctions by token id. A token with an auction cannot be moved until it is settled
+pub const AUCTIONS: Map<&str, Auction> = Map::new("auctions");
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct Offer {
+    pub token_id: String,
+    pub bidder: Addr,
+    /// Held by the contract until the offer is accepted or withdrawn
+    pub amount: Uint64,
+    /// The offer can no longer be accepted after this
+    pub expires: Expiration,
+}
+
+pub struct OfferIndexes<'a> {
+    pub bidder: MultiIndex<'a, Addr, Offer, (String, Addr)>,
+}
+
+impl<'a> IndexList<Offer> for OfferIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Offer>> + '_> {
+        let v: Vec<&dyn Index<Offer>> = vec![&self.bidder];
+        Box::new(v.into_iter())
+    }
+}
+
+/// Offers stored as (token_id, bidder), at most one per bidder on each token
+pub fn offers<'a>() -> IndexedMap<'a, (&'a str, &'a Addr), Offer, OfferIndexes<'a>> {
+    let indexes = OfferIndexes {
+        bidder: MultiIndex::new(|o: &Offer| o.bidder.clone(), "offers", "offers__bidder"),
+    };
+    IndexedMap::new("offers", indexes)
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct CollectionOffer {
+    pub id: u64,
+    pub bidder: Addr,
+    /// Held by the contract until the offer is filled or withdrawn
+    pub amount: Uint64,
+    /// The offer can no longer be filled after this
+    pub expires: Expiration,
+}
+
+pub struct CollectionOfferIndexes<'a> {
+    pub price: MultiIndex<'a, u64, CollectionOffer, u64>,
+}
+
+impl<'a> IndexList<CollectionOffer> for CollectionOfferIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<CollectionOffer>> + '_> {
+        let v: Vec<&dyn Index<CollectionOffer>> = vec![&self.price];
+        Box::new(v.into_iter())
+    }
+}
+
+/// Offers on any token of the collection by offer id, indexed by amount
+pub fn collection_offers<'a>() -> IndexedMap<'a, u64, CollectionOffer, CollectionOfferIndexes<'a>> {
+    let indexes = CollectionOfferIndexes {
+        price: MultiIndex::new(
+            |o: &CollectionOffer| o.amount.u64(),
+            "collection_offers",
+            "collection_offers__price",
+        ),
+    };
+    IndexedMap::new("collection_offers", indexes)
+}
+
+/// Id of the last collection offer made
+pub const COLLECTION_OFFER_COUNT: Item<u64> = Item::new("collection_offer_count");
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct Sale {
+    pub seller: Addr,
+    pub buyer: Addr,
+    pub price: Uint64,
+    pub time: Timestamp,
+}
+
+/// Number of most recent sales kept for each token
+pub const MAX_SALE_HISTORY: u64 = 100;
+
+/// Sales by (token_id, sale number), only the last `MAX_SALE_HISTORY` of a token are kept
+pub const SALE_HISTORY: Map<(&str, u64), Sale> = Map::new("sale_history");
+
+/// Number of sales recorded for each token
+pub const SALE_COUNT: Map<&str, u64> = Map::new("sale_count");
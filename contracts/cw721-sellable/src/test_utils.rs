@@ -1,78 +1,100 @@
-#[cfg(test)]
-pub mod test_utils {
-    use crate::error::ContractError;
-    use crate::msg::Cw721SellableQueryMsg;
-    use crate::{entry, Cw721SellableContract, ExecuteMsg};
-    use cosmwasm_std::testing::{
-        mock_dependencies, mock_dependencies_with_balances, mock_env, mock_info, MockApi,
-        MockQuerier, MockStorage,
-    };
-    use cosmwasm_std::{from_binary, Coin, MessageInfo, OwnedDeps, Response, StdResult};
-    use serde::de::DeserializeOwned;
+#![cfg(test)]
+use crate::error::ContractError;
+use crate::{entry, Cw721SellableContract, ExecuteMsg, Metadata, QueryMsg};
+use cosmwasm_std::testing::{
+    mock_dependencies, mock_dependencies_with_balances, mock_env, mock_info, MockApi, MockQuerier,
+    MockStorage,
+};
+use cosmwasm_std::{from_binary, Coin, Env, MessageInfo, OwnedDeps, Response, StdResult};
+use serde::de::DeserializeOwned;
 
-    pub fn hello() -> String {
-        return "hello".to_string();
-    }
-    pub struct Context<'a> {
-        pub deps: OwnedDeps<MockStorage, MockApi, MockQuerier>,
-        pub contract: Cw721SellableContract<'a>,
-    }
+pub struct Context<'a> {
+    pub deps: OwnedDeps<MockStorage, MockApi, MockQuerier>,
+    pub contract: Cw721SellableContract<'a>,
+    /// Environment used for every call, move `env.block` forward to simulate time passing
+    pub env: Env,
+}
 
-    pub struct ContractInfo {
-        pub name: String,
-        pub symbol: String,
-    }
+pub struct ContractInfo {
+    pub name: String,
+    pub symbol: String,
+}
 
-    impl Context<'_> {
-        pub fn new<'a>(
-            contract_info: ContractInfo,
-            creator: &'a str,
-            balances: Option<&[(&str, &[Coin])]>,
-        ) -> Context<'a> {
-            let mut deps = if let Some(balances) = balances {
-                mock_dependencies_with_balances(balances)
-            } else {
-                mock_dependencies()
-            };
+impl Context<'_> {
+    pub fn new<'a>(
+        contract_info: ContractInfo,
+        creator: &'a str,
+        balances: Option<&[(&str, &[Coin])]>,
+    ) -> Context<'a> {
+        let mut deps = if let Some(balances) = balances {
+            mock_dependencies_with_balances(balances)
+        } else {
+            mock_dependencies()
+        };
 
-            let contract = Cw721SellableContract::default();
-            let creator_info = mock_info(creator, &[]);
-            let init_msg = cw721_base::InstantiateMsg {
-                name: contract_info.name,
-                symbol: contract_info.symbol,
-                minter: creator.to_string(),
-            };
-            contract
-                .instantiate(deps.as_mut(), mock_env(), creator_info.clone(), init_msg)
-                .unwrap();
+        let contract = Cw721SellableContract::default();
+        let creator_info = mock_info(creator, &[]);
+        let init_msg = cw721_base::InstantiateMsg {
+            name: contract_info.name,
+            symbol: contract_info.symbol,
+            minter: creator.to_string(),
+            soulbound: false,
+            contract_metadata: None,
+        };
+        contract
+            .instantiate(deps.as_mut(), mock_env(), creator_info, init_msg)
+            .unwrap();
 
-            Context { deps, contract }
+        Context {
+            deps,
+            contract,
+            env: mock_env(),
         }
+    }
 
-        pub fn execute(
-            &mut self,
-            creator_info: MessageInfo,
-            msg: ExecuteMsg,
-        ) -> Result<Response, ContractError> {
-            entry::execute(self.deps.as_mut(), mock_env(), creator_info, msg)
-        }
+    pub fn execute(
+        &mut self,
+        creator_info: MessageInfo,
+        msg: ExecuteMsg,
+    ) -> Result<Response, ContractError> {
+        entry::execute(self.deps.as_mut(), self.env.clone(), creator_info, msg)
+    }
 
-        pub fn query<T: DeserializeOwned>(&self, msg: Cw721SellableQueryMsg) -> StdResult<T> {
-            let binary_res = entry::query(self.deps.as_ref(), mock_env(), msg);
-            binary_res.and_then(|bin| from_binary(&bin))
-        }
+    /// Mints `token_id` to `owner` with the given metadata, `minter` has to be the contract minter
+    pub fn mint(
+        &mut self,
+        minter: &str,
+        token_id: &str,
+        owner: &str,
+        metadata: Metadata,
+    ) -> Result<Response, ContractError> {
+        let mint_msg = cw721_base::MintMsg {
+            token_id: token_id.to_string(),
+            owner: owner.to_string(),
+            token_uri: None,
+            extension: Some(metadata),
+        };
+        self.execute(
+            mock_info(minter, &[]),
+            ExecuteMsg::BaseMsg(cw721_base::ExecuteMsg::Mint(mint_msg)),
+        )
     }
 
-    impl Default for Context<'_> {
-        fn default() -> Self {
-            Context::new(
-                ContractInfo {
-                    name: "SpaceShips".into(),
-                    symbol: "SPACE".into(),
-                },
-                "creator",
-                None,
-            )
-        }
+    pub fn query<T: DeserializeOwned>(&self, msg: impl Into<QueryMsg>) -> StdResult<T> {
+        let binary_res = entry::query(self.deps.as_ref(), self.env.clone(), msg.into());
+        binary_res.and_then(|bin| from_binary(&bin))
+    }
+}
+
+impl Default for Context<'_> {
+    fn default() -> Self {
+        Context::new(
+            ContractInfo {
+                name: "SpaceShips".into(),
+                symbol: "SPACE".into(),
+            },
+            "creator",
+            None,
+        )
     }
 }
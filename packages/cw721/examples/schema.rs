@@ -5,9 +5,10 @@ use cosmwasm_schema::{export_schema, export_schema_with_title, remove_schemas, s
 use cosmwasm_std::Empty;
 
 use cw721::{
-    AllNftInfoResponse, ApprovalResponse, ApprovalsResponse, ContractInfoResponse, Cw721ExecuteMsg,
-    Cw721QueryMsg, Cw721ReceiveMsg, NftInfoResponse, NumTokensResponse, OperatorsResponse,
-    OwnerOfResponse, TokensResponse,
+    AcceptsNftsResponse, AllNftInfoResponse, ApprovalResponse, ApprovalsResponse,
+    ContractInfoResponse, Cw721ExecuteMsg, Cw721QueryMsg, Cw721ReceiveMsg, NftInfoResponse,
+    NumTokensResponse, OperatorsResponse, OwnerOfResponse, ReceiverQueryMsg, TokensResponse,
+    TokensWithInfoResponse,
 };
 
 type Extension = Option<Empty>;
@@ -21,6 +22,8 @@ fn main() {
     export_schema(&schema_for!(Cw721ExecuteMsg), &out_dir);
     export_schema(&schema_for!(Cw721QueryMsg), &out_dir);
     export_schema(&schema_for!(Cw721ReceiveMsg), &out_dir);
+    export_schema(&schema_for!(ReceiverQueryMsg), &out_dir);
+    export_schema(&schema_for!(AcceptsNftsResponse), &out_dir);
     export_schema_with_title(
         &schema_for!(AllNftInfoResponse<Extension>),
         &out_dir,
@@ -38,4 +41,9 @@ fn main() {
     );
     export_schema(&schema_for!(NumTokensResponse), &out_dir);
     export_schema(&schema_for!(TokensResponse), &out_dir);
+    export_schema_with_title(
+        &schema_for!(TokensWithInfoResponse<Extension>),
+        &out_dir,
+        "TokensWithInfoResponse",
+    );
 }
@@ -8,8 +8,10 @@ pub use cw_utils::Expiration;
 pub use crate::msg::Cw721ExecuteMsg;
 pub use crate::query::{
     AllNftInfoResponse, Approval, ApprovalResponse, ApprovalsResponse, ContractInfoResponse,
-    Cw721QueryMsg, NftInfoResponse, NumTokensResponse, OperatorsResponse, OwnerOfResponse,
-    TokensResponse,
+    ContractMetadataResponse, Cw721QueryMsg, NftInfoResponse, NumTokensResponse, OperatorsResponse,
+    OwnerOfResponse, TokensResponse, TokensWithInfoResponse,
 };
-pub use crate::receiver::Cw721ReceiveMsg;
-pub use crate::traits::{CustomMsg, Cw721, Cw721Execute, Cw721Query};
+pub use crate::receiver::{
+    AcceptsNftsResponse, Cw721ReceiveMsg, ReceiverExecuteMsg, ReceiverQueryMsg,
+};
+pub use crate::traits::{CustomMsg, Cw721, Cw721Execute, Cw721Query, Cw721Receiver};
@@ -61,6 +61,21 @@ pub enum Cw721QueryMsg {
         start_after: Option<String>,
         limit: Option<u32>,
     },
+    /// With Enumerable extension.
+    /// Same as `Tokens`, but returns the `AllNftInfo` of every token along with its id.
+    /// Return type: TokensWithInfoResponse.
+    TokensWithInfo {
+        owner: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// With Enumerable extension.
+    /// Same as `AllTokens`, but returns the `AllNftInfo` of every token along with its id.
+    /// Return type: TokensWithInfoResponse.
+    AllTokensWithInfo {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
@@ -105,6 +120,12 @@ pub struct ContractInfoResponse {
     pub symbol: String,
 }
 
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ContractMetadataResponse<M> {
+    /// Contract wide metadata, unset until given at instantiation or updated by the minter
+    pub metadata: Option<M>,
+}
+
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 pub struct NftInfoResponse<T> {
     /// Universal resource identifier for this NFT
@@ -123,6 +144,12 @@ pub struct AllNftInfoResponse<T> {
     pub info: NftInfoResponse<T>,
 }
 
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct TokensWithInfoResponse<T> {
+    /// Pairs of token_id and its info, in the same ordering as `TokensResponse`
+    pub tokens: Vec<(String, AllNftInfoResponse<T>)>,
+}
+
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 pub struct TokensResponse {
     /// Contains all token_ids in lexicographical ordering
@@ -1,7 +1,8 @@
 use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{to_binary, Binary, CosmosMsg, StdResult, WasmMsg};
+use cosmwasm_std::{from_binary, to_binary, Binary, CosmosMsg, StdResult, WasmMsg};
 
 /// Cw721ReceiveMsg should be de/serialized under `Receive()` variant in a ExecuteMsg
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
@@ -32,12 +33,32 @@ impl Cw721ReceiveMsg {
         };
         Ok(execute.into())
     }
+
+    /// decodes the message attached by the sender
+    pub fn decode_msg<T: DeserializeOwned>(&self) -> StdResult<T> {
+        from_binary(&self.msg)
+    }
 }
 
 /// This is just a helper to properly serialize the above message.
-/// The actual receiver should include this variant in the larger ExecuteMsg enum
+/// The actual receiver should include this variant in the larger ExecuteMsg enum,
+/// or use this one when receiving NFTs is all it does
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 #[serde(rename_all = "snake_case")]
-enum ReceiverExecuteMsg {
+pub enum ReceiverExecuteMsg {
     ReceiveNft(Cw721ReceiveMsg),
 }
+
+/// Query a receiver can answer so `SafeSendNft` checks it accepts tokens of
+/// `collection` before sending them. The receiver should include this variant
+/// in its QueryMsg enum
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ReceiverQueryMsg {
+    AcceptsNfts { collection: String },
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct AcceptsNftsResponse {
+    pub accepts: bool,
+}
@@ -4,10 +4,11 @@ use serde::Serialize;
 
 use crate::query::ApprovalResponse;
 use crate::{
-    AllNftInfoResponse, ApprovalsResponse, ContractInfoResponse, NftInfoResponse,
-    NumTokensResponse, OperatorsResponse, OwnerOfResponse, TokensResponse,
+    AllNftInfoResponse, ApprovalsResponse, ContractInfoResponse, ContractMetadataResponse,
+    Cw721ReceiveMsg, NftInfoResponse, NumTokensResponse, OperatorsResponse, OwnerOfResponse,
+    TokensResponse, TokensWithInfoResponse,
 };
-use cosmwasm_std::{Binary, Deps, DepsMut, Empty, Env, MessageInfo, Response, StdResult};
+use cosmwasm_std::{Binary, Deps, DepsMut, Empty, Env, MessageInfo, Response, StdError, StdResult};
 use cw_utils::Expiration;
 
 // TODO: move this somewhere else... ideally cosmwasm-std
@@ -105,6 +106,8 @@ where
 
     fn contract_info(&self, deps: Deps) -> StdResult<ContractInfoResponse>;
 
+    fn contract_metadata(&self, deps: Deps) -> StdResult<ContractMetadataResponse<M>>;
+
     fn num_tokens(&self, deps: Deps) -> StdResult<NumTokensResponse>;
 
     fn nft_info(&self, deps: Deps, token_id: String) -> StdResult<NftInfoResponse<T>>;
@@ -166,4 +169,70 @@ where
         token_id: String,
         include_expired: bool,
     ) -> StdResult<AllNftInfoResponse<T>>;
+
+    fn tokens_with_info(
+        &self,
+        deps: Deps,
+        env: Env,
+        owner: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<TokensWithInfoResponse<T>>;
+
+    fn all_tokens_with_info(
+        &self,
+        deps: Deps,
+        env: Env,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<TokensWithInfoResponse<T>>;
+}
+
+/// Implemented by contracts that NFTs are sent to with `SendNft`.
+/// Their ExecuteMsg should have a `ReceiveNft(Cw721ReceiveMsg)` variant
+/// that calls `execute_receive_nft`
+pub trait Cw721Receiver<C>
+where
+    C: CustomMsg,
+{
+    type Err: ToString + From<StdError>;
+    /// The message senders attach to the NFT
+    type Msg: DeserializeOwned;
+
+    /// Handles a received NFT. `info.sender` is the cw721 contract it belongs to,
+    /// which should be checked before trusting the token, and `sender` the account that sent it
+    fn receive_nft(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        sender: String,
+        token_id: String,
+        msg: Self::Msg,
+    ) -> Result<Response<C>, Self::Err>;
+
+    /// Answers `ReceiverQueryMsg::AcceptsNfts`, which `SafeSendNft` asks before sending.
+    /// Accepts every collection unless overridden
+    fn accepts_nfts(&self, _deps: Deps, _collection: String) -> StdResult<bool> {
+        Ok(true)
+    }
+
+    /// Decodes the attached message and passes the NFT on to `receive_nft`
+    fn execute_receive_nft(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        receive_msg: Cw721ReceiveMsg,
+    ) -> Result<Response<C>, Self::Err> {
+        let msg = receive_msg.decode_msg()?;
+        self.receive_nft(
+            deps,
+            env,
+            info,
+            receive_msg.sender,
+            receive_msg.token_id,
+            msg,
+        )
+    }
 }